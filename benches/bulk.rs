@@ -0,0 +1,69 @@
+//! Bulk operations on a large bitboard, to compare with and without the simd feature:
+//! cargo bench --bench bulk and cargo bench --bench bulk --features simd
+//! On x86_64 the AVX2 kernels are picked at runtime when the processor supports them
+#![feature(test)]
+
+extern crate test;
+
+use bitboards::*;
+use test::{black_box, Bencher};
+
+type Occupancy = Bitboard<16384>;
+
+fn board(seed: u64) -> Box<Occupancy> {
+    let mut board = Occupancy::new_boxed();
+    let mut state = seed;
+    for index in 0..Occupancy::BITS {
+        // xorshift for a reproducible half full board
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        board.assign(index, state & 1 == 1);
+    }
+    board
+}
+
+#[bench]
+fn union(b: &mut Bencher) {
+    let (mut x, y) = (board(1), board(2));
+    b.iter(|| {
+        *x |= &*y;
+        black_box(&x);
+    });
+}
+
+#[bench]
+fn intersection(b: &mut Bencher) {
+    let (mut x, y) = (board(1), board(2));
+    b.iter(|| {
+        *x &= &*y;
+        black_box(&x);
+    });
+}
+
+#[bench]
+fn difference(b: &mut Bencher) {
+    let (mut x, y) = (board(1), board(2));
+    b.iter(|| {
+        *x -= &*y;
+        black_box(&x);
+    });
+}
+
+#[bench]
+fn equality(b: &mut Bencher) {
+    let (x, y) = (board(1), board(1));
+    b.iter(|| black_box(black_box(&x) == black_box(&y)));
+}
+
+#[bench]
+fn popcount(b: &mut Bencher) {
+    let x = board(1);
+    b.iter(|| black_box(black_box(&x).count_ones()));
+}
+
+#[bench]
+fn emptiness(b: &mut Bencher) {
+    let x = Occupancy::new_boxed();
+    b.iter(|| black_box(black_box(&x).is_empty()));
+}
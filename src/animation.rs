@@ -0,0 +1,130 @@
+//! Animated GIF and APNG export of board sequences
+//!
+//! The boards are seen as grids of lines of line_size cells as in the grid module, each cell
+//! being drawn as a square of scale x scale pixels, black when set and white otherwise.
+
+use crate::BitboardInternal;
+use std::borrow::Borrow;
+use std::convert::TryFrom;
+use std::io::{self, Write};
+
+/// How the frames are drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AnimationOptions {
+    /// The side in pixels of a cell
+    pub scale: usize,
+    /// The time a frame is shown, in milliseconds
+    /// GIF delays are in hundredths of a second so they are rounded up to the next 10 ms
+    pub frame_delay_ms: u16,
+}
+
+impl Default for AnimationOptions {
+    fn default() -> Self {
+        Self {
+            scale: 1,
+            frame_delay_ms: 100,
+        }
+    }
+}
+
+/// Returns the (width, height) of the frames in pixels
+fn frame_size<const N: usize, const R: usize>(line_size: usize, options: &AnimationOptions) -> (usize, usize) {
+    assert!(options.scale > 0, "The scale can't be 0");
+    let line_count = BitboardInternal::<N, R>::line_count(line_size);
+    (line_size * options.scale, line_count * options.scale)
+}
+
+/// Returns a pixel per byte, set_value for the set cells and unset_value for the others
+fn pixels<const N: usize, const R: usize>(
+    board: &BitboardInternal<N, R>,
+    line_size: usize,
+    options: &AnimationOptions,
+    set_value: u8,
+    unset_value: u8,
+) -> Vec<u8> {
+    let (width, height) = frame_size::<N, R>(line_size, options);
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let cell = (y / options.scale) * line_size + x / options.scale;
+            pixels.push(if board.is_set(cell) { set_value } else { unset_value });
+        }
+    }
+    pixels
+}
+
+/// Writes the boards as a looping animated GIF
+pub fn write_gif<W, B, I, const N: usize, const R: usize>(
+    writer: W,
+    frames: I,
+    line_size: usize,
+    options: &AnimationOptions,
+) -> io::Result<()>
+where
+    W: Write,
+    B: Borrow<BitboardInternal<N, R>>,
+    I: IntoIterator<Item = B>,
+{
+    let (width, height) = frame_size::<N, R>(line_size, options);
+    let (width, height) = (gif_dimension(width)?, gif_dimension(height)?);
+    let to_io = |error: gif::EncodingError| io::Error::other(error);
+
+    // white then black
+    let palette = [255, 255, 255, 0, 0, 0];
+    let mut encoder = gif::Encoder::new(writer, width, height, &palette).map_err(to_io)?;
+    encoder.set_repeat(gif::Repeat::Infinite).map_err(to_io)?;
+    for board in frames {
+        let pixels = pixels(board.borrow(), line_size, options, 1, 0);
+        let mut frame = gif::Frame::from_indexed_pixels(width, height, pixels, None);
+        // in hundredths of a second, a 0 delay is shown as fast as possible by most viewers
+        frame.delay = options.frame_delay_ms.div_ceil(10).max(1);
+        encoder.write_frame(&frame).map_err(to_io)?;
+    }
+    Ok(())
+}
+
+/// Writes the boards as a looping animated PNG
+pub fn write_apng<W, B, I, const N: usize, const R: usize>(
+    writer: W,
+    frames: I,
+    line_size: usize,
+    options: &AnimationOptions,
+) -> io::Result<()>
+where
+    W: Write,
+    B: Borrow<BitboardInternal<N, R>>,
+    I: IntoIterator<Item = B>,
+{
+    let (width, height) = frame_size::<N, R>(line_size, options);
+    let (width, height) = (png_dimension(width)?, png_dimension(height)?);
+    // the number of frames goes in the header
+    let frames: Vec<B> = frames.into_iter().collect();
+    if frames.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "An animation needs a frame"));
+    }
+
+    let frame_count = u32::try_from(frames.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many frames for an APNG"))?;
+
+    let mut encoder = png::Encoder::new(writer, width, height);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_animated(frame_count, 0)?;
+    encoder.set_frame_delay(options.frame_delay_ms, 1000)?;
+    let mut writer = encoder.write_header()?;
+    for board in frames.iter() {
+        writer.write_image_data(&pixels(board.borrow(), line_size, options, 0, 255))?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Checks that a GIF dimension fits in 16 bits
+fn gif_dimension(size: usize) -> io::Result<u16> {
+    u16::try_from(size).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "The frames are too large for a GIF"))
+}
+
+/// Checks that a PNG dimension fits in 32 bits
+fn png_dimension(size: usize) -> io::Result<u32> {
+    u32::try_from(size).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "The frames are too large for a PNG"))
+}
@@ -0,0 +1,69 @@
+//! Bitboards as fixed width unsigned integers
+//!
+//! The bit i of a bitboard stands for 2^i so the first word holds the least significant bits,
+//! as with shift_left_one. The bitboard is a BITS bits integer, the junk bits are ignored.
+
+use crate::BitboardInternal;
+use std::ops::MulAssign;
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Returns the full product of the two integers, on twice as many words so it never
+    /// overflows, M is inferred from the result type as in Bitboard<1024> for Bitboard<512>
+    pub fn widening_mul<const M: usize>(&self, other: &Self) -> BitboardInternal<M, 63> {
+        assert_eq!(M, 2 * N, "The product has twice as many words");
+        let mut result = BitboardInternal::<M, 63>::new();
+        for i in 0..N {
+            let x = self.integer_word(i) as u128;
+            if x == 0 {
+                continue;
+            }
+            // x * y + two words is at most 2^128 - 1
+            let mut carry = 0;
+            for j in 0..N {
+                let t = x * other.integer_word(j) as u128 + result.words[i + j] as u128 + carry;
+                result.words[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            result.words[i + N] = carry as u64;
+        }
+        result
+    }
+
+    /// Returns the ith word of the integer, the junk bits unset
+    #[inline]
+    fn integer_word(&self, index: usize) -> u64 {
+        if index == N - 1 {
+            self.words[index] & Self::LAST_WORD_MASK
+        } else {
+            self.words[index]
+        }
+    }
+
+    /// Adds other to the bitboard word by word, the overflow of the last word is deleted
+    fn wrapping_add_words(&mut self, other: &Self) {
+        let mut carry = false;
+        for (word, &other_word) in self.words.iter_mut().zip(other.words.iter()) {
+            let (sum, first_carry) = word.overflowing_add(other_word);
+            let (sum, second_carry) = sum.overflowing_add(carry as u64);
+            *word = sum;
+            carry = first_carry || second_carry;
+        }
+    }
+}
+
+/// Multiplication as unsigned integers, truncated to the size of the bitboard
+/// This shifts and adds the bitboard for each set bit of other
+impl<const N: usize, const R: usize> MulAssign<&BitboardInternal<N, R>> for BitboardInternal<N, R> {
+    fn mul_assign(&mut self, other: &Self) {
+        let mut addend = *self;
+        self.words = [0; N];
+        let mut shifted = 0;
+        for index in other.set_indices() {
+            addend.shift_left_by(index - shifted);
+            shifted = index;
+            self.wrapping_add_words(&addend);
+        }
+        // the bits past BITS are the overflow
+        self.words[N - 1] &= Self::LAST_WORD_MASK;
+    }
+}
@@ -0,0 +1,114 @@
+//! Life-like cellular automata
+//!
+//! A rule tells for how many alive neighbors (out of the 8 around) a dead cell becomes alive
+//! and an alive cell stays alive. The neighbor counts of all the cells are computed at once,
+//! bit-sliced: the k-th plane holds the k-th bit of the count of every cell.
+
+use crate::grid::{Direction, Grid};
+use crate::BitboardInternal;
+
+/// A birth/survival rule, bit k of each mask being set for a count of k neighbors
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rule {
+    pub birth: u16,
+    pub survival: u16,
+}
+
+impl Rule {
+    /// The Conway's Game of Life, B3/S23
+    pub const LIFE: Rule = Rule {
+        birth: 1 << 3,
+        survival: 1 << 2 | 1 << 3,
+    };
+
+    /// Creates the rule from the neighbor counts, which have to be at most 8
+    pub fn new(birth: &[u8], survival: &[u8]) -> Self {
+        let mask = |counts: &[u8]| {
+            counts.iter().fold(0u16, |mask, &count| {
+                assert!(count <= 8, "A cell has at most 8 neighbors");
+                mask | 1 << count
+            })
+        };
+        Self {
+            birth: mask(birth),
+            survival: mask(survival),
+        }
+    }
+
+    /// Parses a rule in the B/S notation, as "B3/S23" or "B36/S23"
+    /// Returns None if the string is not a valid rule
+    pub fn parse(rule: &str) -> Option<Self> {
+        let mut parts = rule.split('/');
+        let birth = parts.next()?;
+        let survival = parts.next()?;
+        if parts.next().is_some() {
+            return None;
+        }
+
+        let mask = |part: &str, prefix: char| {
+            let mut chars = part.chars();
+            if !chars.next()?.eq_ignore_ascii_case(&prefix) {
+                return None;
+            }
+            chars.try_fold(0u16, |mask, c| match c.to_digit(10) {
+                Some(count) if count <= 8 => Some(mask | 1 << count),
+                _ => None,
+            })
+        };
+        Some(Self {
+            birth: mask(birth, 'B')?,
+            survival: mask(survival, 'S')?,
+        })
+    }
+}
+
+impl<const N: usize, const R: usize> Grid<N, R> {
+    /// Returns the next generation of the board under the rule, the neighbors across the edges
+    /// depend on the topology of the grid
+    pub fn automaton_step(&self, board: &BitboardInternal<N, R>, rule: &Rule) -> BitboardInternal<N, R> {
+        let mut alive = *board;
+        alive &= self.cells();
+
+        // adds the neighbors one by one to the 4 bits counters
+        let mut counts = [[0u64; N]; 4];
+        for &direction in Direction::ALL.iter() {
+            add_to_counters(&mut counts, &self.shift(&alive, direction));
+        }
+
+        let mut next = BitboardInternal::new();
+        for i in 0..N {
+            let mut word = 0;
+            for count in 0..=8 {
+                let born = rule.birth >> count & 1 == 1;
+                let survives = rule.survival >> count & 1 == 1;
+                if !born && !survives {
+                    continue;
+                }
+                let with_count = counts.iter().enumerate().fold(!0, |with_count, (bit, plane)| {
+                    with_count & if count >> bit & 1 == 1 { plane[i] } else { !plane[i] }
+                });
+                let cells = match (born, survives) {
+                    (true, true) => !0,
+                    (true, false) => !alive.words[i],
+                    _ => alive.words[i],
+                };
+                word |= with_count & cells;
+            }
+            next.words[i] = word & self.cells().words[i];
+        }
+        next
+    }
+}
+
+/// Adds the bits of the board to bit-sliced counters, the plane i holding the bit i of the count
+/// of each cell
+pub(crate) fn add_to_counters<const N: usize, const R: usize>(counts: &mut [[u64; N]; 4], board: &BitboardInternal<N, R>) {
+    for (i, &word) in board.words.iter().enumerate() {
+        let mut carry = word;
+        for plane in counts.iter_mut() {
+            let next_carry = plane[i] & carry;
+            plane[i] ^= carry;
+            carry = next_carry;
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! Bitboards with their 2D geometry in the type
+//!
+//! Bitboard2D<W, H> is a bitboard of H lines of W cells, laid out line by line as in the grid
+//! module. Its shifts unset the cells moving across the left and right edges instead of letting
+//! them wrap to the next line. For a runtime line size or a wrapping topology use a Grid instead,
+//! Bitboard2D::grid returns the bounded one of the same geometry.
+
+use crate::automaton::add_to_counters;
+use crate::{BitIndex, BitboardInternal, Connectivity, Coord, Direction, Grid};
+
+/// A bitboard of H lines of W cells
+pub type Bitboard2D<const W: usize, const H: usize> = Bitboard2DInternal<{ (W * H - 1) / 64 + 1 }, { (W * H - 1) % 64 }, W>;
+
+/// Internal structure for Bitboard2D, the lines of W cells fill the BITS bits of the bitboard
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Bitboard2DInternal<const N: usize, const R: usize, const W: usize> {
+    board: BitboardInternal<N, R>,
+}
+
+impl<const N: usize, const R: usize, const W: usize> Bitboard2DInternal<N, R, W> {
+    /// The number of cells in a line
+    pub const WIDTH: usize = W;
+    /// The number of lines
+    pub const HEIGHT: usize = BitboardInternal::<N, R>::BITS / W;
+    /// The cells of the first and the last column, unset by the horizontal shifts
+    const FIRST_COLUMN: BitboardInternal<N, R> = Self::column_mask(0).board;
+    const LAST_COLUMN: BitboardInternal<N, R> = Self::column_mask(W - 1).board;
+
+    /// Creates a new empty board
+    #[inline]
+    pub const fn new() -> Self {
+        Self::from_board(BitboardInternal::new())
+    }
+
+    /// Wraps a bitboard whose bits are the cells line by line
+    #[inline]
+    pub const fn from_board(board: BitboardInternal<N, R>) -> Self {
+        Self { board }
+    }
+
+    /// Returns the underlying bitboard
+    #[inline]
+    pub fn board(&self) -> &BitboardInternal<N, R> {
+        &self.board
+    }
+    /// Returns the underlying bitboard mutably
+    #[inline]
+    pub fn board_mut(&mut self) -> &mut BitboardInternal<N, R> {
+        &mut self.board
+    }
+    /// Unwraps the underlying bitboard
+    #[inline]
+    pub fn into_board(self) -> BitboardInternal<N, R> {
+        self.board
+    }
+
+    /// Returns the bounded grid of the same geometry
+    pub fn grid() -> Grid<N, R> {
+        Grid::new(W)
+    }
+
+    /// Returns the index of the cell (x, y)
+    #[inline]
+    const fn index(x: usize, y: usize) -> usize {
+        assert!(x < W && y < Self::HEIGHT, "Cell out of the board");
+        y * W + x
+    }
+    /// Returns the flat index of a typed index of the board
+    #[inline]
+    fn flat<I: BitIndex>(index: I) -> usize {
+        let flat = index.to_flat(W);
+        assert!(flat < W * Self::HEIGHT, "Cell out of the board");
+        flat
+    }
+
+    /// Sets the given cell, Coord(x, y) or its flat index
+    #[inline]
+    pub fn set<I: BitIndex>(&mut self, index: I) {
+        self.board.set(Self::flat(index));
+    }
+    /// Unsets the given cell, Coord(x, y) or its flat index
+    #[inline]
+    pub fn unset<I: BitIndex>(&mut self, index: I) {
+        self.board.unset(Self::flat(index));
+    }
+    /// Returns wether or not the given cell is set, Coord(x, y) or its flat index
+    #[inline]
+    pub fn is_set<I: BitIndex>(&self, index: I) -> bool {
+        self.board.is_set(Self::flat(index))
+    }
+
+    /// Returns the board with all the cells of the line y set
+    pub fn row_mask(y: usize) -> Self {
+        let mut mask = Self::new();
+        mask.board.set_range(Self::index(0, y)..Self::index(0, y) + W);
+        mask
+    }
+    /// Returns the board with all the cells of the column x set
+    pub const fn column_mask(x: usize) -> Self {
+        let mut mask = Self::new();
+        let mut y = 0;
+        while y < Self::HEIGHT {
+            mask.board = mask.board.with_bit(Self::index(x, y));
+            y += 1;
+        }
+        mask
+    }
+
+    /// Moves every cell one line up, the first line is lost
+    #[inline]
+    pub fn shift_north(&self) -> Self {
+        Self::from_board(self.board >> W)
+    }
+    /// Moves every cell one line down, the last line is lost
+    #[inline]
+    pub fn shift_south(&self) -> Self {
+        Self::from_board(self.board << W)
+    }
+    /// Moves every cell one column right, the last column is lost
+    #[inline]
+    pub fn shift_east(&self) -> Self {
+        let mut board = self.board << 1;
+        board -= &Self::FIRST_COLUMN;
+        Self::from_board(board)
+    }
+    /// Moves every cell one column left, the first column is lost
+    #[inline]
+    pub fn shift_west(&self) -> Self {
+        let mut board = self.board >> 1;
+        board -= &Self::LAST_COLUMN;
+        Self::from_board(board)
+    }
+
+    /// Moves every cell one step in the given direction, the cells going out of the board are
+    /// lost
+    pub fn shift(&self, direction: Direction) -> Self {
+        match direction {
+            Direction::North => self.shift_north(),
+            Direction::South => self.shift_south(),
+            Direction::East => self.shift_east(),
+            Direction::West => self.shift_west(),
+            Direction::NorthEast => self.shift_east().shift_north(),
+            Direction::NorthWest => self.shift_west().shift_north(),
+            Direction::SouthEast => self.shift_east().shift_south(),
+            Direction::SouthWest => self.shift_west().shift_south(),
+        }
+    }
+
+    /// Returns the number of set neighbors of the given cell, Coord(x, y) or its flat index
+    pub fn neighbor_count<I: BitIndex>(&self, index: I, connectivity: Connectivity) -> usize {
+        let Coord(x, y) = Coord::from_flat(Self::flat(index), W);
+        let (x, y) = (x as isize, y as isize);
+        Self::directions(connectivity)
+            .iter()
+            .map(|direction| {
+                let (dx, dy) = direction.offset();
+                (x + dx, y + dy)
+            })
+            .filter(|&(x, y)| x >= 0 && y >= 0 && (x as usize) < W && (y as usize) < Self::HEIGHT)
+            .filter(|&(x, y)| self.is_set(Coord(x as usize, y as usize)))
+            .count()
+    }
+
+    /// Returns the neighbor counts of all the cells as bit planes, the plane i holding the bit i
+    /// of the count of each cell
+    pub fn neighbor_counts(&self, connectivity: Connectivity) -> [Self; 4] {
+        let mut counts = [[0; N]; 4];
+        for &direction in Self::directions(connectivity) {
+            add_to_counters(&mut counts, &self.shift(direction).board);
+        }
+        counts.map(|words| Self::from_board(BitboardInternal { words }))
+    }
+
+    /// Returns the cells having exactly count set neighbors
+    pub fn with_neighbor_count(&self, count: usize, connectivity: Connectivity) -> Self {
+        // the counters only hold 4 bits, no cell has more neighbors than directions anyway
+        if count > Self::directions(connectivity).len() {
+            return Self::new();
+        }
+        let mut result = !Self::new().board;
+        for (bit, plane) in self.neighbor_counts(connectivity).iter().enumerate() {
+            if count >> bit & 1 == 1 {
+                result &= plane.board;
+            } else {
+                result &= !plane.board;
+            }
+        }
+        Self::from_board(result)
+    }
+
+    /// Returns the directions of the neighbors
+    fn directions(connectivity: Connectivity) -> &'static [Direction] {
+        match connectivity {
+            Connectivity::Four => &Direction::ALL[..4],
+            Connectivity::Eight => &Direction::ALL,
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Word by word kernels of the bulk operations
+//!
+//! With the simd feature the words go through std::simd vectors of LANES words and the last
+//! ones, fewer than LANES, are processed one by one. On x86_64 the slices of at least
+//! DISPATCH_WORDS words run a copy of the kernel compiled for AVX2 when the processor supports
+//! it, which is detected at runtime, and the baseline copy otherwise, whose vectors are split in
+//! smaller ones. Without the feature the loops are scalar.
+
+#[cfg(feature = "simd")]
+use std::simd::{cmp::SimdPartialEq, num::SimdUint, Simd};
+
+/// The number of words of a vector
+#[cfg(feature = "simd")]
+const LANES: usize = 8;
+#[cfg(feature = "simd")]
+type Vector = Simd<u64, LANES>;
+
+/// The shorter slices always run the baseline kernels, the detection would cost more than the
+/// wider instructions save
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+const DISPATCH_WORDS: usize = 4 * LANES;
+
+/// Returns the number of words processed by vectors
+#[cfg(feature = "simd")]
+#[inline(always)]
+fn vector_len(len: usize) -> usize {
+    len - len % LANES
+}
+
+/// Defines a kernel whose body is compiled twice on x86_64 with the simd feature, for the
+/// baseline instructions and for AVX2, the copy being chosen at runtime from the first slice
+/// length and the processor features
+macro_rules! kernel {
+    ($(#[$doc:meta])* fn $name:ident($words:ident: $words_type:ty $(, $arg:ident: $type:ty)*) $(-> $ret:ty)? $body:block) => {
+        $(#[$doc])*
+        #[inline]
+        pub(crate) fn $name($words: $words_type $(, $arg: $type)*) $(-> $ret)? {
+            #[inline(always)]
+            fn baseline($words: $words_type $(, $arg: $type)*) $(-> $ret)? $body
+
+            #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+            {
+                #[target_feature(enable = "avx2")]
+                unsafe fn avx2($words: $words_type $(, $arg: $type)*) $(-> $ret)? {
+                    baseline($words $(, $arg)*)
+                }
+                if $words.len() >= DISPATCH_WORDS && std::is_x86_feature_detected!("avx2") {
+                    // the processor supports AVX2
+                    return unsafe { avx2($words $(, $arg)*) };
+                }
+            }
+            baseline($words $(, $arg)*)
+        }
+    };
+}
+
+/// Defines a kernel combining words with the same size other words in place, the body being
+/// valid for both u64 and vectors
+macro_rules! assign_kernel {
+    ($(#[$doc:meta])* $name:ident, |$a:ident, $b:ident| $body:expr) => {
+        kernel! {
+            $(#[$doc])*
+            fn $name(words: &mut [u64], other: &[u64]) {
+                let len = words.len().min(other.len());
+                #[cfg(feature = "simd")]
+                let start = {
+                    let end = vector_len(len);
+                    let chunks = words[..end].chunks_exact_mut(LANES).zip(other[..end].chunks_exact(LANES));
+                    for (chunk, other_chunk) in chunks {
+                        let ($a, $b) = (Vector::from_slice(chunk), Vector::from_slice(other_chunk));
+                        ($body).copy_to_slice(chunk);
+                    }
+                    end
+                };
+                #[cfg(not(feature = "simd"))]
+                let start = 0;
+                for (word, &other_word) in words[start..len].iter_mut().zip(&other[start..len]) {
+                    let ($a, $b) = (*word, other_word);
+                    *word = $body;
+                }
+            }
+        }
+    };
+}
+
+assign_kernel!(
+    /// Intersection
+    and_assign, |a, b| a & b
+);
+assign_kernel!(
+    /// Union
+    or_assign, |a, b| a | b
+);
+assign_kernel!(
+    /// Symmetric difference
+    xor_assign, |a, b| a ^ b
+);
+assign_kernel!(
+    /// Difference
+    and_not_assign, |a, b| a & !b
+);
+
+kernel! {
+    /// Returns the number of set bits of the words
+    fn count_ones(words: &[u64]) -> usize {
+        #[cfg(feature = "simd")]
+        let (count, start) = {
+            let end = vector_len(words.len());
+            let mut counts = Vector::splat(0);
+            for chunk in words[..end].chunks_exact(LANES) {
+                counts += Vector::from_slice(chunk).count_ones();
+            }
+            (counts.reduce_sum() as usize, end)
+        };
+        #[cfg(not(feature = "simd"))]
+        let (count, start) = (0, 0);
+        count + words[start..].iter().map(|word| word.count_ones() as usize).sum::<usize>()
+    }
+}
+
+kernel! {
+    /// Returns wether all the words are 0
+    fn is_zero(words: &[u64]) -> bool {
+        #[cfg(feature = "simd")]
+        let (any, start) = {
+            let end = vector_len(words.len());
+            let mut any = Vector::splat(0);
+            for chunk in words[..end].chunks_exact(LANES) {
+                any |= Vector::from_slice(chunk);
+            }
+            (any.reduce_or() != 0, end)
+        };
+        #[cfg(not(feature = "simd"))]
+        let (any, start) = (false, 0);
+        !any && words[start..].iter().all(|&word| word == 0)
+    }
+}
+
+kernel! {
+    /// Returns wether the words are equal to the same size other words
+    fn equal(words: &[u64], other: &[u64]) -> bool {
+        debug_assert_eq!(words.len(), other.len());
+        #[cfg(feature = "simd")]
+        let (different, start) = {
+            let end = vector_len(words.len());
+            let mut differences = Vector::splat(0);
+            let chunks = words[..end].chunks_exact(LANES).zip(other[..end].chunks_exact(LANES));
+            for (chunk, other_chunk) in chunks {
+                differences |= Vector::from_slice(chunk) ^ Vector::from_slice(other_chunk);
+            }
+            (differences.simd_ne(Vector::splat(0)).any(), end)
+        };
+        #[cfg(not(feature = "simd"))]
+        let (different, start) = (false, 0);
+        !different && words[start..] == other[start..]
+    }
+}
@@ -0,0 +1,42 @@
+//! Stable checksum of the bitboard contents
+
+use crate::BitboardInternal;
+
+/// Reflected polynomial of CRC-64/XZ
+const POLYNOMIAL: u64 = 0xc96c_5795_d787_0f42;
+
+const TABLE: [u64; 256] = crc_table();
+
+const fn crc_table() -> [u64; 256] {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut crc = byte as u64;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ POLYNOMIAL } else { crc >> 1 };
+            bit += 1;
+        }
+        table[byte] = crc;
+        byte += 1;
+    }
+    table
+}
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Returns the CRC-64/XZ of the canonical bytes of the bitboard
+    /// The canonical bytes are the BITS / 8 rounded up bytes holding the valid bits, the bit i
+    /// being the bit i % 8 of the byte i / 8, with the junk bits of the last byte cleared.
+    /// This only depends on the valid bits, not on the platform nor on the junk bits.
+    pub fn checksum64(&self) -> u64 {
+        let len = Self::BITS.div_ceil(8);
+
+        let mut crc = !0;
+        for index in 0..len {
+            let width = 8.min(Self::BITS - 8 * index);
+            let byte = self.get_field(8 * index, width);
+            crc = TABLE[((crc ^ byte) & 0xff) as usize] ^ (crc >> 8);
+        }
+        !crc
+    }
+}
@@ -0,0 +1,71 @@
+//! Chess helpers for Bitboard<64>
+//!
+//! Squares are indexed rank by rank from a1 = 0 to h8 = 63, so b1 = 1 and a2 = 8.
+
+use crate::{BitIndex, BitboardInternal, BoardIndex};
+
+const SQUARE_NAMES: [&str; 64] = [
+    "a1", "b1", "c1", "d1", "e1", "f1", "g1", "h1",
+    "a2", "b2", "c2", "d2", "e2", "f2", "g2", "h2",
+    "a3", "b3", "c3", "d3", "e3", "f3", "g3", "h3",
+    "a4", "b4", "c4", "d4", "e4", "f4", "g4", "h4",
+    "a5", "b5", "c5", "d5", "e5", "f5", "g5", "h5",
+    "a6", "b6", "c6", "d6", "e6", "f6", "g6", "h6",
+    "a7", "b7", "c7", "d7", "e7", "f7", "g7", "h7",
+    "a8", "b8", "c8", "d8", "e8", "f8", "g8", "h8",
+];
+
+/// Returns the algebraic name of the given square
+#[inline]
+pub fn square_name(square: usize) -> &'static str {
+    SQUARE_NAMES[square]
+}
+
+/// Returns the square with the given algebraic name, like "e4"
+pub fn square_index(name: &str) -> Option<usize> {
+    match name.as_bytes() {
+        &[file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Some((rank - b'1') as usize * 8 + (file - b'a') as usize),
+        _ => None,
+    }
+}
+
+/// A square as a typed index, for a grid of lines of 8 cells
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Square(pub u8);
+
+impl Square {
+    /// Returns the square with the given algebraic name, like "e4"
+    pub fn from_name(name: &str) -> Option<Self> {
+        square_index(name).map(|index| Square(index as u8))
+    }
+
+    /// Returns the algebraic name of the square
+    #[inline]
+    pub fn name(self) -> &'static str {
+        square_name(self.0 as usize)
+    }
+}
+
+impl BitIndex for Square {
+    #[inline]
+    fn to_flat(self, line_size: usize) -> usize {
+        assert_eq!(line_size, 8, "A chess board has lines of 8 squares");
+        self.0 as usize
+    }
+    #[inline]
+    fn from_flat(index: usize, _line_size: usize) -> Self {
+        Square(index as u8)
+    }
+}
+
+/// Squares index Bitboard<64> directly, as lines of 8 squares
+impl BoardIndex<BitboardInternal<1, 63>> for Square {
+    const LINE_SIZE: usize = 8;
+}
+
+impl BitboardInternal<1, 63> {
+    /// Returns an iterator over the algebraic names of the set squares, from a1 to h8
+    pub fn ones_squares(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.set_indices().map(square_name)
+    }
+}
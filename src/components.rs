@@ -0,0 +1,180 @@
+//! Connected components of a 2D board kept up to date cell by cell
+//!
+//! The set cells are kept in a union-find. Setting a cell merges it with its neighbors right
+//! away, while unsetting a cell may split its component: the union-find is then rebuilt from
+//! scratch at the next query, so a batch of removals costs a single rebuild.
+
+use crate::grid::{Connectivity, Direction, Grid};
+use crate::{BitIndex, BitboardInternal};
+
+/// The connected components of the set cells of a board on a grid
+/// The cells are given as Coord or flat indices, see the index module
+#[derive(Clone)]
+pub struct ComponentTracker<const N: usize, const R: usize> {
+    grid: Grid<N, R>,
+    connectivity: Connectivity,
+    board: BitboardInternal<N, R>,
+    // union-find forest over the cell indices, only meaningful for the set cells
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    component_count: usize,
+    // wether a cell was unset since the last rebuild
+    stale: bool,
+}
+
+impl<const N: usize, const R: usize> ComponentTracker<N, R> {
+    /// Creates the tracker of an empty board
+    pub fn new(grid: Grid<N, R>, connectivity: Connectivity) -> Self {
+        Self::from_board(grid, connectivity, BitboardInternal::new())
+    }
+
+    /// Creates the tracker of the components of the board, restricted to the grid cells
+    pub fn from_board(grid: Grid<N, R>, connectivity: Connectivity, mut board: BitboardInternal<N, R>) -> Self {
+        board &= grid.cells();
+        let mut tracker = Self {
+            grid,
+            connectivity,
+            board,
+            parent: (0..BitboardInternal::<N, R>::BITS).collect(),
+            size: vec![1; BitboardInternal::<N, R>::BITS],
+            component_count: 0,
+            stale: true,
+        };
+        tracker.rebuild();
+        tracker
+    }
+
+    /// Returns the tracked board
+    #[inline]
+    pub fn board(&self) -> &BitboardInternal<N, R> {
+        &self.board
+    }
+
+    /// Returns the flat index of a typed index of the grid
+    #[inline]
+    fn flat<I: BitIndex>(&self, index: I) -> usize {
+        index.to_flat(self.grid.line_size())
+    }
+
+    /// Sets a cell, Coord(x, y) or its flat index, merging the components around it
+    pub fn set<I: BitIndex>(&mut self, index: I) {
+        let index = self.flat(index);
+        assert!(self.grid.cells().is_set(index), "The cell is out of the grid");
+        if self.board.is_set(index) {
+            return;
+        }
+        self.board.set(index);
+        // the rebuild will take care of the new cell
+        if self.stale {
+            return;
+        }
+
+        self.parent[index] = index;
+        self.size[index] = 1;
+        self.component_count += 1;
+        self.union_with_neighbors(index);
+    }
+
+    /// Unsets a cell, Coord(x, y) or its flat index, its component will be split if needed at
+    /// the next query
+    pub fn unset<I: BitIndex>(&mut self, index: I) {
+        let index = self.flat(index);
+        if self.board.is_set(index) {
+            self.board.unset(index);
+            self.stale = true;
+        }
+    }
+
+    /// Returns the number of components
+    pub fn component_count(&mut self) -> usize {
+        self.rebuild();
+        self.component_count
+    }
+
+    /// Returns the label of the component of a cell, None if the cell is unset
+    /// The labels are cells of the components and are only stable until the next modification
+    pub fn label<I: BitIndex>(&mut self, index: I) -> Option<usize> {
+        let index = self.flat(index);
+        if self.board.is_unset(index) {
+            return None;
+        }
+        self.rebuild();
+        Some(self.find(index))
+    }
+
+    /// Returns wether two set cells are in the same component
+    pub fn connected<I: BitIndex>(&mut self, a: I, b: I) -> bool {
+        match (self.label(a), self.label(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Returns the cells of the component of a cell, empty if the cell is unset
+    pub fn component<I: BitIndex>(&mut self, index: I) -> BitboardInternal<N, R> {
+        let mut component = BitboardInternal::new();
+        if let Some(label) = self.label(index) {
+            let cells: Vec<_> = self.board.set_indices().collect();
+            for cell in cells {
+                if self.find(cell) == label {
+                    component.set(cell);
+                }
+            }
+        }
+        component
+    }
+
+    /// Relabels all the cells if some were unset
+    fn rebuild(&mut self) {
+        if !self.stale {
+            return;
+        }
+        self.stale = false;
+
+        let cells: Vec<_> = self.board.set_indices().collect();
+        for &cell in cells.iter() {
+            self.parent[cell] = cell;
+            self.size[cell] = 1;
+        }
+        self.component_count = cells.len();
+        for cell in cells {
+            self.union_with_neighbors(cell);
+        }
+    }
+
+    fn union_with_neighbors(&mut self, index: usize) {
+        let directions = match self.connectivity {
+            Connectivity::Four => &Direction::ALL[..4],
+            Connectivity::Eight => &Direction::ALL[..],
+        };
+        for &direction in directions {
+            if let Some(neighbor) = self.grid.neighbor(index, direction) {
+                if self.board.is_set(neighbor) {
+                    self.union(index, neighbor);
+                }
+            }
+        }
+    }
+
+    fn find(&mut self, mut index: usize) -> usize {
+        while self.parent[index] != index {
+            // path halving
+            self.parent[index] = self.parent[self.parent[index]];
+            index = self.parent[index];
+        }
+        index
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.component_count -= 1;
+    }
+}
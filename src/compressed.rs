@@ -0,0 +1,303 @@
+//! Run-length compressed bitboards
+//!
+//! The words of the bitboard are stored EWAH style: a sequence of markers, each one standing
+//! for a run of identical empty or full words followed by a number of literal words stored
+//! verbatim. Boolean operations walk both run sequences at once, so long runs are combined in
+//! a single step without being decompressed.
+
+use crate::{word_ones, BitboardInternal};
+use std::ops::{BitAnd, BitOr, BitXor};
+
+/// A compressed bitboard of L bits
+/// L has to be different than 0
+pub type CompressedBitboard<const L: usize> = CompressedBitboardInternal<{(L-1) / 64 + 1}, {(L-1) % 64}>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Marker {
+    fill: bool,
+    fill_words: usize,
+    literal_words: usize,
+}
+
+/// Internal structure for CompressedBitboard, N and R are the ones of the dense bitboard
+/// The junk bits are always cleared so the representation of a set of bits is unique
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompressedBitboardInternal<const N: usize, const R: usize> {
+    markers: Vec<Marker>,
+    literals: Vec<u64>,
+}
+
+/// A part of a marker, whole runs or a single literal word
+enum Piece {
+    Fill(bool, usize),
+    Literal(u64),
+}
+
+/// Reads the words of a compressed bitboard piece by piece
+struct Cursor<'a> {
+    markers: &'a [Marker],
+    literals: &'a [u64],
+    marker: usize,
+    // number of words already read in the current marker
+    offset: usize,
+    // index of the first literal of the current marker
+    literal_base: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new<const N: usize, const R: usize>(board: &'a CompressedBitboardInternal<N, R>) -> Self {
+        Self {
+            markers: &board.markers,
+            literals: &board.literals,
+            marker: 0,
+            offset: 0,
+            literal_base: 0,
+        }
+    }
+
+    /// Returns the piece at the cursor position, a fill piece covers the rest of its run
+    /// The cursor has to be before the end of the bitboard
+    fn peek(&self) -> Piece {
+        let marker = &self.markers[self.marker];
+        if self.offset < marker.fill_words {
+            Piece::Fill(marker.fill, marker.fill_words - self.offset)
+        } else {
+            Piece::Literal(self.literals[self.literal_base + self.offset - marker.fill_words])
+        }
+    }
+
+    /// Moves forward by the given number of words, it can't go past the current piece
+    fn advance(&mut self, words: usize) {
+        self.offset += words;
+        let marker = &self.markers[self.marker];
+        if self.offset == marker.fill_words + marker.literal_words {
+            self.literal_base += marker.literal_words;
+            self.marker += 1;
+            self.offset = 0;
+        }
+    }
+}
+
+impl<const N: usize, const R: usize> CompressedBitboardInternal<N, R> {
+    /// Creates a new empty compressed bitboard
+    pub fn new() -> Self {
+        let mut result = Self::empty();
+        result.push_words(0, N);
+        result
+    }
+
+    /// Compresses a dense bitboard
+    pub fn from_bitboard(board: &BitboardInternal<N, R>) -> Self {
+        let mut result = Self::empty();
+        for (index, &word) in board.words.iter().enumerate() {
+            result.push_words(word & Self::word_mask(index), 1);
+        }
+        result
+    }
+
+    /// Decompresses into a dense bitboard, allocated on the heap
+    pub fn to_bitboard(&self) -> Box<BitboardInternal<N, R>> {
+        let mut board = BitboardInternal::new_boxed();
+        let mut cursor = Cursor::new(self);
+        let mut index = 0;
+        while index < N {
+            let words = match cursor.peek() {
+                Piece::Fill(fill, words) => {
+                    for word in board.words[index..index + words].iter_mut() {
+                        *word = fill_word(fill);
+                    }
+                    words
+                }
+                Piece::Literal(word) => {
+                    board.words[index] = word;
+                    1
+                }
+            };
+            cursor.advance(words);
+            index += words;
+        }
+        board
+    }
+
+    /// Returns the number of words used by the compressed representation, counting a marker
+    /// as a single word
+    pub fn compressed_words(&self) -> usize {
+        self.markers.len() + self.literals.len()
+    }
+
+    /// Returns wether or not the given bit is set, this is linear in the number of markers
+    pub fn is_set(&self, index: usize) -> bool {
+        self.words()
+            .take_while(|&(word_index, _)| word_index <= index / 64)
+            .any(|(word_index, word)| word_index == index / 64 && word & (1 << (index % 64)) != 0)
+    }
+
+    /// Returns wether the compressed bitboard is empty
+    pub fn is_empty(&self) -> bool {
+        self.literals.is_empty() && self.markers.iter().all(|marker| !marker.fill)
+    }
+
+    /// Returns the number of set bits
+    pub fn count_ones(&self) -> usize {
+        let fills: usize = self.markers.iter().filter(|marker| marker.fill).map(|marker| marker.fill_words * 64).sum();
+        let literals: usize = self.literals.iter().map(|word| word.count_ones() as usize).sum();
+        fills + literals
+    }
+
+    /// Returns the number of bytes allocated on the heap, the unused capacity included
+    pub fn heap_size_bytes(&self) -> usize {
+        self.markers.capacity() * std::mem::size_of::<Marker>() + self.literals.capacity() * 8
+    }
+
+    /// Releases the unused capacity
+    pub fn shrink_to_fit(&mut self) {
+        self.markers.shrink_to_fit();
+        self.literals.shrink_to_fit();
+    }
+
+    /// Returns the proportion of set bits
+    pub fn density(&self) -> f64 {
+        self.count_ones() as f64 / BitboardInternal::<N, R>::BITS as f64
+    }
+
+    /// Returns an iterator over the indices of the set bits, in increasing order
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words().flat_map(|(index, word)| word_ones(word, index * 64))
+    }
+
+    /// Returns an iterator over the non empty words along with their index
+    fn words(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        let mut word_index = 0;
+        let mut literal_index = 0;
+        self.markers.iter().flat_map(move |marker| {
+            let fill_start = word_index;
+            let literal_start = literal_index;
+            word_index += marker.fill_words + marker.literal_words;
+            literal_index += marker.literal_words;
+
+            let fill_words = if marker.fill { marker.fill_words } else { 0 };
+            let fills = (fill_start..fill_start + fill_words).map(|index| (index, !0));
+            let literals = (0..marker.literal_words)
+                .map(move |offset| (fill_start + marker.fill_words + offset, self.literals[literal_start + offset]));
+            fills.chain(literals)
+        })
+    }
+
+    /// Combines two compressed bitboards word by word, runs are combined as a whole
+    fn combine<F: Fn(u64, u64) -> u64>(&self, other: &Self, op: F) -> Self {
+        let mut result = Self::empty();
+        let mut cursor = Cursor::new(self);
+        let mut other_cursor = Cursor::new(other);
+        let mut index = 0;
+        while index < N {
+            let (word, words) = match (cursor.peek(), other_cursor.peek()) {
+                (Piece::Fill(fill, words), Piece::Fill(other_fill, other_words)) => {
+                    (op(fill_word(fill), fill_word(other_fill)), words.min(other_words))
+                }
+                (Piece::Fill(fill, _), Piece::Literal(other_word)) => (op(fill_word(fill), other_word), 1),
+                (Piece::Literal(word), Piece::Fill(other_fill, _)) => (op(word, fill_word(other_fill)), 1),
+                (Piece::Literal(word), Piece::Literal(other_word)) => (op(word, other_word), 1),
+            };
+            // the combination of two full last words may be full even with junk bits
+            let masked_words = if index + words == N { words - 1 } else { words };
+            result.push_words(word, masked_words);
+            if masked_words < words {
+                result.push_words(word & Self::word_mask(N - 1), 1);
+            }
+
+            cursor.advance(words);
+            other_cursor.advance(words);
+            index += words;
+        }
+        result
+    }
+
+    fn empty() -> Self {
+        Self {
+            markers: Vec::new(),
+            literals: Vec::new(),
+        }
+    }
+
+    /// Appends count copies of the given word
+    fn push_words(&mut self, word: u64, count: usize) {
+        if count == 0 {
+            return;
+        }
+        if word == 0 || word == !0 {
+            let fill = word == !0;
+            if let Some(last) = self.markers.last_mut() {
+                if last.literal_words == 0 && (last.fill == fill || last.fill_words == 0) {
+                    last.fill = fill;
+                    last.fill_words += count;
+                    return;
+                }
+            }
+            self.markers.push(Marker {
+                fill,
+                fill_words: count,
+                literal_words: 0,
+            });
+        } else {
+            if self.markers.is_empty() {
+                self.markers.push(Marker {
+                    fill: false,
+                    fill_words: 0,
+                    literal_words: 0,
+                });
+            }
+            self.markers.last_mut().unwrap().literal_words += count;
+            self.literals.extend(std::iter::repeat_n(word, count));
+        }
+    }
+
+    /// Returns the mask of the valid bits of the given word
+    #[inline]
+    fn word_mask(index: usize) -> u64 {
+        if index == N - 1 {
+            BitboardInternal::<N, R>::LAST_WORD_MASK
+        } else {
+            !0
+        }
+    }
+}
+
+#[inline]
+fn fill_word(fill: bool) -> u64 {
+    if fill {
+        !0
+    } else {
+        0
+    }
+}
+
+impl<const N: usize, const R: usize> Default for CompressedBitboardInternal<N, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Intersection between two compressed bitboards
+impl<const N: usize, const R: usize> BitAnd for &CompressedBitboardInternal<N, R> {
+    type Output = CompressedBitboardInternal<N, R>;
+
+    fn bitand(self, other: Self) -> Self::Output {
+        self.combine(other, |a, b| a & b)
+    }
+}
+/// Union between two compressed bitboards
+impl<const N: usize, const R: usize> BitOr for &CompressedBitboardInternal<N, R> {
+    type Output = CompressedBitboardInternal<N, R>;
+
+    fn bitor(self, other: Self) -> Self::Output {
+        self.combine(other, |a, b| a | b)
+    }
+}
+/// Symmetric difference between two compressed bitboards
+impl<const N: usize, const R: usize> BitXor for &CompressedBitboardInternal<N, R> {
+    type Output = CompressedBitboardInternal<N, R>;
+
+    fn bitxor(self, other: Self) -> Self::Output {
+        self.combine(other, |a, b| a ^ b)
+    }
+}
@@ -0,0 +1,131 @@
+//! 2 bits per base DNA encoding
+//!
+//! Bases are packed in sequence order from the LSBs of the bitboard with A = 0, C = 1, G = 2
+//! and T = 3, so that a k-mer read as a packed field has its first base in its lowest bits.
+
+use crate::{compress_even_bits, BitboardInternal};
+
+/// A packed DNA sequence of at most L bases
+/// L has to be different than 0
+pub type PackedDna<const L: usize> = PackedDnaInternal<{(2*L-1) / 64 + 1}, {(2*L-1) % 64}>;
+
+/// Internal structure for PackedDna, N and R are the ones of the underlying bitboard
+#[derive(Clone)]
+pub struct PackedDnaInternal<const N: usize, const R: usize> {
+    bits: BitboardInternal<N, R>,
+    len: usize,
+}
+
+/// Returns the 2 bits code of a base, case insensitive
+#[inline]
+pub fn encode_base(base: u8) -> Option<u64> {
+    match base {
+        b'A' | b'a' => Some(0),
+        b'C' | b'c' => Some(1),
+        b'G' | b'g' => Some(2),
+        b'T' | b't' => Some(3),
+        _ => None,
+    }
+}
+
+/// Returns the upper case base of a 2 bits code
+#[inline]
+pub fn decode_base(code: u64) -> u8 {
+    b"ACGT"[(code & 0b11) as usize]
+}
+
+impl<const N: usize, const R: usize> PackedDnaInternal<N, R> {
+    /// Packs the given sequence, returns None if it contains something else than ACGT
+    /// The sequence has to fit in the bitboard
+    pub fn from_bases(bases: &[u8]) -> Option<Self> {
+        assert!(2 * bases.len() <= BitboardInternal::<N, R>::BITS);
+
+        let mut bits = BitboardInternal::new();
+        for (index, &base) in bases.iter().enumerate() {
+            bits.set_field(2 * index, 2, encode_base(base)?);
+        }
+
+        Some(Self {
+            bits,
+            len: bases.len(),
+        })
+    }
+
+    /// Returns the number of bases
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns wether the sequence is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the underlying bitboard
+    #[inline]
+    pub fn as_bitboard(&self) -> &BitboardInternal<N, R> {
+        &self.bits
+    }
+
+    /// Returns the upper case base at the given position
+    #[inline]
+    pub fn base(&self, index: usize) -> u8 {
+        assert!(index < self.len);
+        decode_base(self.bits.get_field(2 * index, 2))
+    }
+
+    /// Returns the k-mer starting at the given position as an integer, k is at most 32
+    #[inline]
+    pub fn kmer(&self, index: usize, k: usize) -> u64 {
+        assert!(k <= 32 && index + k <= self.len);
+        self.bits.get_field(2 * index, 2 * k)
+    }
+
+    /// Returns an iterator over all the k-mers of the sequence, in order
+    pub fn kmers(&self, k: usize) -> impl Iterator<Item = u64> + '_ {
+        assert!(k <= 32);
+        (0..(self.len + 1).saturating_sub(k)).map(move |index| self.kmer(index, k))
+    }
+
+    /// Returns a bitboard with the bit i set if the base i is the given one
+    /// The resulting bitboard has to be at least as long as the sequence
+    pub fn base_mask<const M: usize, const S: usize>(&self, base: u8) -> BitboardInternal<M, S> {
+        let code = encode_base(base).expect("not a DNA base");
+        // the base code repeated in every 2 bits slot
+        let repeated = code * 0x5555_5555_5555_5555;
+        self.gather_matches(|index| self.bits.words[index] ^ repeated)
+    }
+
+    /// Returns a bitboard with the bit i set if both sequences have the same base i
+    /// The resulting bitboard has to be at least as long as the shortest sequence
+    pub fn match_mask<const M: usize, const S: usize>(&self, query: &Self) -> BitboardInternal<M, S> {
+        let len = self.len.min(query.len);
+        let mut mask = self.gather_matches(|index| self.bits.words[index] ^ query.bits.words[index]);
+        // the bases past the shortest sequence are both junk
+        for index in len..self.len {
+            mask.unset(index);
+        }
+        mask
+    }
+
+    /// Builds a board with one bit per base, set where the 2 bits slot of difference is 0
+    fn gather_matches<const M: usize, const S: usize, F>(&self, difference: F) -> BitboardInternal<M, S>
+    where
+        F: Fn(usize) -> u64,
+    {
+        assert!(self.len <= BitboardInternal::<M, S>::BITS);
+
+        let mut mask = BitboardInternal::new();
+        for word_index in 0..(2 * self.len).div_ceil(64) {
+            let difference = difference(word_index);
+            let same = !(difference | (difference >> 1)) & 0x5555_5555_5555_5555;
+
+            let first_base = 32 * word_index;
+            let bases = 32.min(self.len - first_base);
+            mask.set_field(first_base, bases, compress_even_bits(same));
+        }
+        mask
+    }
+}
@@ -0,0 +1,637 @@
+//! Bitboards with a size chosen at runtime
+//!
+//! DynBitboard has the operations of BitboardInternal on words stored in a Vec. Unlike the
+//! const sized bitboards, its junk bits (past its length in the last word) are always unset.
+//!
+//! With the allocator_api feature the words can live in any allocator, an arena or huge pages
+//! for instance, with DynBitboard::new_in. Without it the allocator parameter can only be the
+//! Global stand-in of this module.
+
+use crate::bulk;
+use crate::{
+    apply_range, read_field, select_in_words, shift_words_left, shift_words_right, write_field, BitIterator, BitView,
+    BitViewMut, BitboardInternal, BoardIndex,
+};
+#[cfg(feature = "allocator_api")]
+pub use std::alloc::Global;
+#[cfg(feature = "allocator_api")]
+use std::alloc::Allocator;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{
+    AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Index, Not, Range, Shl, ShlAssign, Shr,
+    ShrAssign, Sub, SubAssign,
+};
+
+/// The global allocator, the only one available without the allocator_api feature
+#[cfg(not(feature = "allocator_api"))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Global;
+
+/// The allocators the words of a DynBitboard can be stored in
+#[cfg(feature = "allocator_api")]
+pub trait BoardAllocator: Allocator + Clone {}
+#[cfg(feature = "allocator_api")]
+impl<A: Allocator + Clone> BoardAllocator for A {}
+/// The allocators the words of a DynBitboard can be stored in
+#[cfg(not(feature = "allocator_api"))]
+pub trait BoardAllocator: Clone {}
+#[cfg(not(feature = "allocator_api"))]
+impl BoardAllocator for Global {}
+
+/// A bitboard of len bits, len being known at runtime only
+#[derive(Clone)]
+pub struct DynBitboard<A: BoardAllocator = Global> {
+    #[cfg(feature = "allocator_api")]
+    words: Vec<u64, A>,
+    #[cfg(not(feature = "allocator_api"))]
+    words: Vec<u64>,
+    #[cfg(not(feature = "allocator_api"))]
+    allocator: A,
+    len: usize,
+}
+
+impl DynBitboard {
+    /// Creates a new empty bitboard of len bits
+    pub fn new(len: usize) -> Self {
+        Self::zeroed_in(len, Global)
+    }
+}
+
+impl<A: BoardAllocator> DynBitboard<A> {
+    /// Creates a new empty bitboard of len bits with its words in the given allocator
+    #[cfg(feature = "allocator_api")]
+    pub fn new_in(len: usize, allocator: A) -> Self {
+        Self::zeroed_in(len, allocator)
+    }
+
+    /// Returns the allocator of the words
+    #[cfg(feature = "allocator_api")]
+    pub fn allocator(&self) -> &A {
+        self.words.allocator()
+    }
+
+    #[cfg(feature = "allocator_api")]
+    fn zeroed_in(len: usize, allocator: A) -> Self {
+        let mut words = Vec::with_capacity_in(len.div_ceil(64), allocator);
+        words.resize(len.div_ceil(64), 0);
+        Self { words, len }
+    }
+    #[cfg(not(feature = "allocator_api"))]
+    fn zeroed_in(len: usize, allocator: A) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+            allocator,
+            len,
+        }
+    }
+
+    /// Returns an empty bitboard of the same length in the same allocator
+    pub(crate) fn empty_like(&self) -> Self {
+        #[cfg(feature = "allocator_api")]
+        let allocator = self.words.allocator().clone();
+        #[cfg(not(feature = "allocator_api"))]
+        let allocator = self.allocator.clone();
+        Self::zeroed_in(self.len, allocator)
+    }
+
+    /// Returns the number of bits of the bitboard
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the words of the bitboard, the bit i being the bit i % 64 of the word i / 64
+    /// The junk bits of the last word are always unset
+    #[inline]
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Appends a bit at the end of the bitboard
+    pub fn push(&mut self, bit: bool) {
+        if self.len.is_multiple_of(64) {
+            self.words.push(0);
+        }
+        self.len += 1;
+        self.assign(self.len - 1, bit);
+    }
+
+    /// Changes the length of the bitboard, the new bits are set to value
+    pub fn resize(&mut self, new_len: usize, value: bool) {
+        if new_len <= self.len {
+            self.truncate(new_len);
+            return;
+        }
+        let old_len = self.len;
+        self.words.resize(new_len.div_ceil(64), if value { !0 } else { 0 });
+        self.len = new_len;
+        if value {
+            // the junk of the old last word, now valid bits
+            let old_end = (old_len.div_ceil(64) * 64).min(new_len);
+            for index in old_len..old_end {
+                self.set(index);
+            }
+            self.clear_junk();
+        }
+    }
+
+    /// Shortens the bitboard to len bits, nothing happens if it is already shorter
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
+        self.words.truncate(len.div_ceil(64));
+        self.len = len;
+        self.clear_junk();
+    }
+
+    /// Sets the given bit of the bitboard, a flat index or a typed one, see BoardIndex
+    #[inline]
+    pub fn set<I: BoardIndex<Self>>(&mut self, index: I) {
+        let index = index.to_flat(I::LINE_SIZE);
+        assert!(index < self.len, "Index out of the bitboard");
+        self.words[index / 64] |= 1 << (index % 64);
+    }
+    /// Unsets the given bit of the bitboard, a flat index or a typed one, see BoardIndex
+    #[inline]
+    pub fn unset<I: BoardIndex<Self>>(&mut self, index: I) {
+        let index = index.to_flat(I::LINE_SIZE);
+        assert!(index < self.len, "Index out of the bitboard");
+        self.words[index / 64] &= !(1 << (index % 64));
+    }
+    /// Sets or unsets the given bit of the bitboard depending on value
+    #[inline]
+    pub fn assign<I: BoardIndex<Self>>(&mut self, index: I, value: bool) {
+        if value {
+            self.set(index);
+        } else {
+            self.unset(index);
+        }
+    }
+    /// Returns wether or not the given bit is set
+    #[inline]
+    pub fn is_set<I: BoardIndex<Self>>(&self, index: I) -> bool {
+        let index = index.to_flat(I::LINE_SIZE);
+        assert!(index < self.len, "Index out of the bitboard");
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+    /// Returns wether or not the given bit is unset
+    #[inline]
+    pub fn is_unset<I: BoardIndex<Self>>(&self, index: I) -> bool {
+        !self.is_set(index)
+    }
+    /// Sets all the bits of the range
+    #[inline]
+    pub fn set_range(&mut self, range: Range<usize>) {
+        assert!(range.start <= range.end && range.end <= self.len, "Range out of the bitboard");
+        apply_range(&mut self.words, range, |word, mask| word | mask);
+    }
+    /// Unsets all the bits of the range
+    #[inline]
+    pub fn unset_range(&mut self, range: Range<usize>) {
+        assert!(range.start <= range.end && range.end <= self.len, "Range out of the bitboard");
+        apply_range(&mut self.words, range, |word, mask| word & !mask);
+    }
+    /// Flips all the bits of the range
+    #[inline]
+    pub fn toggle_range(&mut self, range: Range<usize>) {
+        assert!(range.start <= range.end && range.end <= self.len, "Range out of the bitboard");
+        apply_range(&mut self.words, range, |word, mask| word ^ mask);
+    }
+    /// Returns wether no bit is set, as BitboardInternal::is_empty
+    pub fn is_empty(&self) -> bool {
+        bulk::is_zero(&self.words)
+    }
+    /// Flip the whole bitboard (equivalent to a not to itself)
+    pub fn flip(&mut self) {
+        for word in self.words.iter_mut() {
+            *word = !*word;
+        }
+        self.clear_junk();
+    }
+
+    /// Returns an iterator over the indices of the set bits
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.iter()
+    }
+
+    /// Returns an iterator over the indices of the set bits, that can also go from the end
+    #[inline]
+    pub fn iter(&self) -> BitIterator<&[u64]> {
+        // the junk bits are always unset
+        BitIterator::new(&self.words, !0)
+    }
+
+    /// Returns the number of set bits
+    pub fn count_ones(&self) -> usize {
+        bulk::count_ones(&self.words)
+    }
+    /// Returns the index of the first set bit, None if the bitboard is empty
+    #[inline]
+    pub fn first_set(&self) -> Option<usize> {
+        self.iter().next()
+    }
+    /// Returns the index of the last set bit, None if the bitboard is empty
+    #[inline]
+    pub fn last_set(&self) -> Option<usize> {
+        self.iter().next_back()
+    }
+    /// Returns the index of the nth set bit counting from 0, None if there are not that many
+    pub fn select(&self, n: usize) -> Option<usize> {
+        select_in_words(self.words.iter().copied(), n)
+    }
+
+    /// Returns the number of bytes allocated on the heap, the unused capacity included
+    pub fn heap_size_bytes(&self) -> usize {
+        self.words.capacity() * 8
+    }
+
+    /// Releases the unused capacity, after a truncate for instance
+    pub fn shrink_to_fit(&mut self) {
+        self.words.shrink_to_fit();
+    }
+
+    /// Returns the proportion of set bits, 0 for an empty bitboard
+    pub fn density(&self) -> f64 {
+        if self.len == 0 {
+            return 0.0;
+        }
+        self.count_ones() as f64 / self.len as f64
+    }
+
+    /// Returns the width bits starting at the given index as the LSBs of a word
+    /// The field can span two words of the bitboard
+    #[inline]
+    pub fn get_field(&self, index: usize, width: usize) -> u64 {
+        assert!(index + width <= self.len, "Field out of the bitboard");
+        read_field(&self.words, index, width)
+    }
+    /// Overwrites the width bits starting at the given index with the LSBs of value
+    /// The field can span two words of the bitboard
+    #[inline]
+    pub fn set_field(&mut self, index: usize, width: usize, value: u64) {
+        assert!(index + width <= self.len, "Field out of the bitboard");
+        write_field(&mut self.words, index, width, value);
+    }
+
+    /// Returns a read only view of the given range of bits
+    #[inline]
+    pub fn view(&self, range: Range<usize>) -> BitView<'_> {
+        assert!(range.start <= range.end && range.end <= self.len);
+        BitView::new(&self.words, range.start, range.end - range.start)
+    }
+
+    /// Returns a mutable view of the given range of bits, it can be split with
+    /// BitViewMut::split_at_mut
+    #[inline]
+    pub fn view_mut(&mut self, range: Range<usize>) -> BitViewMut<'_> {
+        assert!(range.start <= range.end && range.end <= self.len);
+        BitViewMut::new(&mut self.words, range.start, range.end - range.start)
+    }
+
+    /// Returns a new bitboard where the bit i of this one is moved to the position perm[i]
+    /// perm has to be a permutation of the indices of the bitboard
+    pub fn permute(&self, perm: &[usize]) -> Self {
+        assert_eq!(perm.len(), self.len);
+
+        let mut result = self.empty_like();
+        for index in self.ones() {
+            result.set(perm[index]);
+        }
+        result
+    }
+
+    /// Returns the bitboard with the order of its bits reversed, the bit i goes to len - 1 - i
+    pub fn reverse_bits(&self) -> Self {
+        let mut result = self.empty_like();
+        for index in self.ones() {
+            result.set(self.len - 1 - index);
+        }
+        result
+    }
+
+    /// Unsets the bits past the length in the last word
+    fn clear_junk(&mut self) {
+        if !self.len.is_multiple_of(64) {
+            let last = self.words.len() - 1;
+            self.words[last] &= !0 >> (64 - self.len % 64);
+        }
+    }
+}
+
+impl Default for DynBitboard {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<A: BoardAllocator> fmt::Debug for DynBitboard<A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynBitboard")
+            .field("words", &&self.words[..])
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+/// Equality of the bits, wherever they are allocated
+impl<A: BoardAllocator, B: BoardAllocator> PartialEq<DynBitboard<B>> for DynBitboard<A> {
+    fn eq(&self, other: &DynBitboard<B>) -> bool {
+        self.len == other.len && self.words[..] == other.words[..]
+    }
+}
+impl<A: BoardAllocator> Eq for DynBitboard<A> {}
+
+impl<A: BoardAllocator> Hash for DynBitboard<A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.words[..].hash(state);
+        self.len.hash(state);
+    }
+}
+
+impl<'a, A: BoardAllocator> IntoIterator for &'a DynBitboard<A> {
+    type Item = usize;
+    type IntoIter = BitIterator<&'a [u64]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Reading a bit with board[index], this is the same as is_set
+impl<I: BoardIndex<DynBitboard<A>>, A: BoardAllocator> Index<I> for DynBitboard<A> {
+    type Output = bool;
+
+    #[inline]
+    fn index(&self, index: I) -> &bool {
+        if self.is_set(index) {
+            &true
+        } else {
+            &false
+        }
+    }
+}
+
+/// Union between two same size sets of bits
+impl<A: BoardAllocator> AddAssign<&Self> for DynBitboard<A> {
+    fn add_assign(&mut self, other: &Self) {
+        assert_eq!(self.len, other.len, "The bitboards have different sizes");
+        bulk::or_assign(&mut self.words, &other.words);
+    }
+}
+impl<A: BoardAllocator> AddAssign for DynBitboard<A> {
+    #[inline]
+    fn add_assign(&mut self, other: Self) {
+        *self += &other;
+    }
+}
+/// Set substraction between two same size sets of bits
+impl<A: BoardAllocator> SubAssign<&Self> for DynBitboard<A> {
+    fn sub_assign(&mut self, other: &Self) {
+        assert_eq!(self.len, other.len, "The bitboards have different sizes");
+        bulk::and_not_assign(&mut self.words, &other.words);
+    }
+}
+impl<A: BoardAllocator> SubAssign for DynBitboard<A> {
+    #[inline]
+    fn sub_assign(&mut self, other: Self) {
+        *self -= &other;
+    }
+}
+
+/// Implements a word by word operator and its assign variant, by value and by reference,
+/// between two bitboards of the same size
+/// The junk bits of the result are unset
+macro_rules! bitwise_operator {
+    ($op_trait:ident, $op:ident, $assign_trait:ident, $assign:ident, $kernel:path) => {
+        impl<A: BoardAllocator> $assign_trait<&Self> for DynBitboard<A> {
+            #[inline]
+            fn $assign(&mut self, other: &Self) {
+                assert_eq!(self.len, other.len, "The bitboards have different sizes");
+                $kernel(&mut self.words, &other.words);
+                self.clear_junk();
+            }
+        }
+        impl<A: BoardAllocator> $assign_trait for DynBitboard<A> {
+            #[inline]
+            fn $assign(&mut self, other: Self) {
+                self.$assign(&other);
+            }
+        }
+        impl<A: BoardAllocator> $op_trait for DynBitboard<A> {
+            type Output = Self;
+
+            #[inline]
+            fn $op(mut self, other: Self) -> Self {
+                self.$assign(&other);
+                self
+            }
+        }
+        impl<A: BoardAllocator> $op_trait for &DynBitboard<A> {
+            type Output = DynBitboard<A>;
+
+            #[inline]
+            fn $op(self, other: Self) -> DynBitboard<A> {
+                let mut result = self.clone();
+                result.$assign(other);
+                result
+            }
+        }
+    };
+}
+
+bitwise_operator!(BitAnd, bitand, BitAndAssign, bitand_assign, bulk::and_assign);
+bitwise_operator!(BitOr, bitor, BitOrAssign, bitor_assign, bulk::or_assign);
+bitwise_operator!(BitXor, bitxor, BitXorAssign, bitxor_assign, bulk::xor_assign);
+
+/// The complement of the set of bits, the junk bits stay unset
+impl<A: BoardAllocator> Not for DynBitboard<A> {
+    type Output = Self;
+
+    #[inline]
+    fn not(mut self) -> Self {
+        self.flip();
+        self
+    }
+}
+impl<A: BoardAllocator> Not for &DynBitboard<A> {
+    type Output = DynBitboard<A>;
+
+    #[inline]
+    fn not(self) -> DynBitboard<A> {
+        !self.clone()
+    }
+}
+
+/// Shift towards the most significant bits, the bits going past len are lost
+impl<A: BoardAllocator> ShlAssign<usize> for DynBitboard<A> {
+    #[inline]
+    fn shl_assign(&mut self, amount: usize) {
+        shift_words_left(&mut self.words, amount);
+        self.clear_junk();
+    }
+}
+/// Shift towards the least significant bits
+impl<A: BoardAllocator> ShrAssign<usize> for DynBitboard<A> {
+    #[inline]
+    fn shr_assign(&mut self, amount: usize) {
+        // the junk bits are always unset so none is shifted in
+        shift_words_right(&mut self.words, amount);
+    }
+}
+impl<A: BoardAllocator> Shl<usize> for DynBitboard<A> {
+    type Output = Self;
+
+    #[inline]
+    fn shl(mut self, amount: usize) -> Self {
+        self <<= amount;
+        self
+    }
+}
+impl<A: BoardAllocator> Shr<usize> for DynBitboard<A> {
+    type Output = Self;
+
+    #[inline]
+    fn shr(mut self, amount: usize) -> Self {
+        self >>= amount;
+        self
+    }
+}
+impl<A: BoardAllocator> Shl<usize> for &DynBitboard<A> {
+    type Output = DynBitboard<A>;
+
+    #[inline]
+    fn shl(self, amount: usize) -> DynBitboard<A> {
+        self.clone() << amount
+    }
+}
+impl<A: BoardAllocator> Shr<usize> for &DynBitboard<A> {
+    type Output = DynBitboard<A>;
+
+    #[inline]
+    fn shr(self, amount: usize) -> DynBitboard<A> {
+        self.clone() >> amount
+    }
+}
+
+/// A const sized bitboard as a runtime sized one, the junk bits are dropped
+impl<const N: usize, const R: usize> From<&BitboardInternal<N, R>> for DynBitboard {
+    fn from(board: &BitboardInternal<N, R>) -> Self {
+        let mut result = Self::new(BitboardInternal::<N, R>::BITS);
+        result.words.copy_from_slice(&board.words);
+        result.words[N - 1] &= BitboardInternal::<N, R>::LAST_WORD_MASK;
+        result
+    }
+}
+
+/// A runtime sized bitboard as a const sized one, the bitboard is given back if the sizes
+/// differ
+impl<A: BoardAllocator, const N: usize, const R: usize> TryFrom<DynBitboard<A>> for BitboardInternal<N, R> {
+    type Error = DynBitboard<A>;
+
+    fn try_from(board: DynBitboard<A>) -> Result<Self, DynBitboard<A>> {
+        if board.len != Self::BITS {
+            return Err(board);
+        }
+        let mut result = Self::new();
+        result.words.copy_from_slice(&board.words);
+        Ok(result)
+    }
+}
+
+/// Junk ignoring equality with a const sized bitboard of the same size
+impl<A: BoardAllocator, const N: usize, const R: usize> PartialEq<BitboardInternal<N, R>> for DynBitboard<A> {
+    fn eq(&self, other: &BitboardInternal<N, R>) -> bool {
+        let last_word = other.words[N - 1] & BitboardInternal::<N, R>::LAST_WORD_MASK;
+        self.len == BitboardInternal::<N, R>::BITS
+            && self.words[..N - 1] == other.words[..N - 1]
+            && self.words[N - 1] == last_word
+    }
+}
+impl<A: BoardAllocator, const N: usize, const R: usize> PartialEq<DynBitboard<A>> for BitboardInternal<N, R> {
+    fn eq(&self, other: &DynBitboard<A>) -> bool {
+        other == self
+    }
+}
+
+/// Union with a const sized bitboard of the same size
+impl<A: BoardAllocator, const N: usize, const R: usize> AddAssign<&BitboardInternal<N, R>> for DynBitboard<A> {
+    fn add_assign(&mut self, other: &BitboardInternal<N, R>) {
+        assert_eq!(self.len, BitboardInternal::<N, R>::BITS, "The bitboards have different sizes");
+        bulk::or_assign(&mut self.words, &other.words);
+        self.clear_junk();
+    }
+}
+/// Union with a runtime sized bitboard of the same size
+impl<A: BoardAllocator, const N: usize, const R: usize> AddAssign<&DynBitboard<A>> for BitboardInternal<N, R> {
+    fn add_assign(&mut self, other: &DynBitboard<A>) {
+        assert_eq!(Self::BITS, other.len, "The bitboards have different sizes");
+        bulk::or_assign(&mut self.words, &other.words);
+    }
+}
+
+/// Implements a word by word operator and its assign variant between a runtime sized and a
+/// const sized bitboard of the same size, in both directions, the result having the type of
+/// the left operand
+/// The junk bits of the result are unset
+macro_rules! mixed_operator {
+    ($op_trait:ident, $op:ident, $assign_trait:ident, $assign:ident, $kernel:path) => {
+        impl<A: BoardAllocator, const N: usize, const R: usize> $assign_trait<&BitboardInternal<N, R>> for DynBitboard<A> {
+            #[inline]
+            fn $assign(&mut self, other: &BitboardInternal<N, R>) {
+                assert_eq!(self.len, BitboardInternal::<N, R>::BITS, "The bitboards have different sizes");
+                $kernel(&mut self.words, &other.words);
+                self.clear_junk();
+            }
+        }
+        impl<A: BoardAllocator, const N: usize, const R: usize> $assign_trait<&DynBitboard<A>> for BitboardInternal<N, R> {
+            #[inline]
+            fn $assign(&mut self, other: &DynBitboard<A>) {
+                assert_eq!(Self::BITS, other.len, "The bitboards have different sizes");
+                $kernel(&mut self.words, &other.words);
+                self.words[N - 1] &= Self::LAST_WORD_MASK;
+            }
+        }
+        impl<A: BoardAllocator, const N: usize, const R: usize> $op_trait<&BitboardInternal<N, R>> for DynBitboard<A> {
+            type Output = Self;
+
+            #[inline]
+            fn $op(mut self, other: &BitboardInternal<N, R>) -> Self {
+                self.$assign(other);
+                self
+            }
+        }
+        impl<A: BoardAllocator, const N: usize, const R: usize> $op_trait<&BitboardInternal<N, R>> for &DynBitboard<A> {
+            type Output = DynBitboard<A>;
+
+            #[inline]
+            fn $op(self, other: &BitboardInternal<N, R>) -> DynBitboard<A> {
+                $op_trait::$op(self.clone(), other)
+            }
+        }
+        impl<A: BoardAllocator, const N: usize, const R: usize> $op_trait<&DynBitboard<A>> for BitboardInternal<N, R> {
+            type Output = Self;
+
+            #[inline]
+            fn $op(mut self, other: &DynBitboard<A>) -> Self {
+                self.$assign(other);
+                self
+            }
+        }
+        impl<A: BoardAllocator, const N: usize, const R: usize> $op_trait<&DynBitboard<A>> for &BitboardInternal<N, R> {
+            type Output = BitboardInternal<N, R>;
+
+            #[inline]
+            fn $op(self, other: &DynBitboard<A>) -> BitboardInternal<N, R> {
+                $op_trait::$op(*self, other)
+            }
+        }
+    };
+}
+
+mixed_operator!(BitAnd, bitand, BitAndAssign, bitand_assign, bulk::and_assign);
+mixed_operator!(BitOr, bitor, BitOrAssign, bitor_assign, bulk::or_assign);
+mixed_operator!(BitXor, bitxor, BitXorAssign, bitxor_assign, bulk::xor_assign);
+mixed_operator!(Sub, sub, SubAssign, sub_assign, bulk::and_not_assign);
@@ -0,0 +1,146 @@
+//! Myers bit-parallel edit distance
+//!
+//! The columns of the dynamic programming matrix are represented by their vertical deltas,
+//! packed in a positive (Pv) and a negative (Mv) bitboard so that a whole column is computed
+//! with a handful of word operations. Patterns longer than 64 bytes are split in blocks of one
+//! word, following Hyyrö's multi-block formulation.
+
+use crate::BitboardInternal;
+
+/// A Myers pattern of at most M bytes
+/// M has to be different than 0
+pub type MyersPattern<const M: usize> = Myers<{(M-1) / 64 + 1}, {(M-1) % 64}>;
+
+/// A compiled Myers pattern, see MyersPattern
+#[derive(Clone)]
+pub struct Myers<const N: usize, const R: usize> {
+    // bit i of peq[c] is set if the pattern has byte c at position i
+    peq: Vec<BitboardInternal<N, R>>,
+    len: usize,
+}
+
+impl<const N: usize, const R: usize> Myers<N, R> {
+    /// Compiles the given pattern into per-byte match masks
+    /// The pattern can't be empty and has to fit in the bitboard
+    pub fn new(pattern: &[u8]) -> Self {
+        assert!(!pattern.is_empty());
+        assert!(pattern.len() <= BitboardInternal::<N, R>::BITS);
+
+        let mut peq = vec![BitboardInternal::new(); 256];
+        for (index, &byte) in pattern.iter().enumerate() {
+            peq[byte as usize].set(index);
+        }
+
+        Self {
+            peq,
+            len: pattern.len(),
+        }
+    }
+
+    /// Returns the length of the compiled pattern
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns wether the compiled pattern is empty, this is always false
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns the Levenshtein distance between the pattern and the given text
+    pub fn distance(&self, text: &[u8]) -> usize {
+        // A band this large is never cut
+        self.distance_within(text, self.len + text.len()).unwrap()
+    }
+
+    /// Banded variant of distance, returns None if the distance is greater than max
+    /// Only the blocks intersecting the diagonal band of width 2 * max + 1 are computed
+    pub fn distance_within(&self, text: &[u8], max: usize) -> Option<usize> {
+        let (m, n) = (self.len, text.len());
+        if m.max(n) - m.min(n) > max {
+            return None;
+        }
+
+        let blocks = (m - 1) / 64 + 1;
+        // last row of the block, as a pattern prefix length
+        let row_end = |block: usize| m.min(64 * block + 64);
+        let last_bit = 1 << ((m - 1) % 64);
+
+        let mut pv = BitboardInternal::<N, R>::new();
+        pv.flip();
+        let mut mv = BitboardInternal::<N, R>::new();
+
+        // Blocks are activated once their first row enters the band, their state is still the
+        // one of the first column, that is an upper bound of the actual column
+        let active_blocks = |column: usize| blocks.min((column + max).div_ceil(64));
+        let mut active = active_blocks(0);
+        // value of the last row of the last active block in the current column
+        let mut score = if active == 0 { 0 } else { row_end(active - 1) } as isize;
+
+        for (index, &byte) in text.iter().enumerate() {
+            let column = index + 1;
+
+            let new_active = active_blocks(column);
+            for block in active..new_active {
+                let previous_end = if block == 0 { 0 } else { row_end(block - 1) };
+                score += (row_end(block) - previous_end) as isize;
+            }
+            active = new_active;
+
+            // Blocks entirely above the band are dropped, the row above the first computed
+            // block is then assumed to increase by one at each column
+            let first = if column > max { (column - max - 1) / 64 } else { 0 };
+
+            let eq = &self.peq[byte as usize];
+            let mut carry = 1;
+            for block in first..active {
+                let high = if block == blocks - 1 { last_bit } else { 1 << 63 };
+                carry = advance_block(&mut pv.words[block], &mut mv.words[block], eq.words[block], carry, high);
+            }
+            score += carry as isize;
+        }
+
+        let score = score as usize;
+        if active == blocks && score <= max {
+            Some(score)
+        } else {
+            None
+        }
+    }
+}
+
+/// Computes the next column of a single word block given the horizontal delta entering it
+/// from the block above, returns the horizontal delta of the row selected by high
+#[inline]
+fn advance_block(pv: &mut u64, mv: &mut u64, eq: u64, hin: i8, high: u64) -> i8 {
+    let xv = eq | *mv;
+    // a negative delta entering the block behaves as a match on its first row
+    let eq = eq | (hin < 0) as u64;
+    let xh = ((eq & *pv).wrapping_add(*pv) ^ *pv) | eq;
+
+    let mut ph = *mv | !(xh | *pv);
+    let mut mh = *pv & xh;
+
+    let hout = if ph & high != 0 {
+        1
+    } else if mh & high != 0 {
+        -1
+    } else {
+        0
+    };
+
+    ph <<= 1;
+    mh <<= 1;
+    if hin < 0 {
+        mh |= 1;
+    } else if hin > 0 {
+        ph |= 1;
+    }
+
+    *pv = mh | !(xv | ph);
+    *mv = ph & xv;
+
+    hout
+}
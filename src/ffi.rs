@@ -0,0 +1,125 @@
+//! C interface
+//!
+//! Bitboards are exposed to C through an opaque heap allocated handle on a DynBitboard whose
+//! size is chosen at creation. Every function taking a handle accepts a null pointer and then
+//! does nothing. The declarations can be generated with cbindgen.
+
+use crate::DynBitboard;
+
+/// Opaque handle on a bitboard
+pub struct BitboardHandle(DynBitboard);
+
+/// Creates a new empty bitboard of the given number of bits, to be freed with bitboard_free
+#[no_mangle]
+pub extern "C" fn bitboard_new(bits: usize) -> *mut BitboardHandle {
+    Box::into_raw(Box::new(BitboardHandle(DynBitboard::new(bits))))
+}
+
+/// Frees a bitboard created by bitboard_new
+///
+/// # Safety
+/// handle has to be null or come from bitboard_new and not be used afterward
+#[no_mangle]
+pub unsafe extern "C" fn bitboard_free(handle: *mut BitboardHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Returns the number of bits of the bitboard
+///
+/// # Safety
+/// handle has to be null or a live handle
+#[no_mangle]
+pub unsafe extern "C" fn bitboard_len(handle: *const BitboardHandle) -> usize {
+    handle.as_ref().map_or(0, |handle| handle.0.len())
+}
+
+/// Sets the given bit, returns false if it is out of the bitboard
+///
+/// # Safety
+/// handle has to be null or a live handle
+#[no_mangle]
+pub unsafe extern "C" fn bitboard_set(handle: *mut BitboardHandle, index: usize) -> bool {
+    match handle.as_mut() {
+        Some(handle) if index < handle.0.len() => {
+            handle.0.set(index);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Unsets the given bit, returns false if it is out of the bitboard
+///
+/// # Safety
+/// handle has to be null or a live handle
+#[no_mangle]
+pub unsafe extern "C" fn bitboard_unset(handle: *mut BitboardHandle, index: usize) -> bool {
+    match handle.as_mut() {
+        Some(handle) if index < handle.0.len() => {
+            handle.0.unset(index);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Returns wether the given bit is set, out of bounds bits are unset
+///
+/// # Safety
+/// handle has to be null or a live handle
+#[no_mangle]
+pub unsafe extern "C" fn bitboard_is_set(handle: *const BitboardHandle, index: usize) -> bool {
+    handle
+        .as_ref()
+        .is_some_and(|handle| index < handle.0.len() && handle.0.is_set(index))
+}
+
+/// Returns the number of set bits
+///
+/// # Safety
+/// handle has to be null or a live handle
+#[no_mangle]
+pub unsafe extern "C" fn bitboard_count_ones(handle: *const BitboardHandle) -> usize {
+    handle.as_ref().map_or(0, |handle| handle.0.count_ones())
+}
+
+/// Intersects target with other in place, returns false if their sizes differ
+///
+/// # Safety
+/// Both handles have to be null or live handles, they can be the same
+#[no_mangle]
+pub unsafe extern "C" fn bitboard_and(target: *mut BitboardHandle, other: *const BitboardHandle) -> bool {
+    combine(target, other, |target, other| *target &= other)
+}
+
+/// Unites target with other in place, returns false if their sizes differ
+///
+/// # Safety
+/// Both handles have to be null or live handles, they can be the same
+#[no_mangle]
+pub unsafe extern "C" fn bitboard_or(target: *mut BitboardHandle, other: *const BitboardHandle) -> bool {
+    combine(target, other, |target, other| *target |= other)
+}
+
+/// Applies the in place operation op once the sizes are checked, op being idempotent
+unsafe fn combine<F: Fn(&mut DynBitboard, &DynBitboard)>(
+    target: *mut BitboardHandle,
+    other: *const BitboardHandle,
+    op: F,
+) -> bool {
+    if target.is_null() || other.is_null() {
+        return false;
+    }
+    // both handles may be the same, a & a and a | a are a so there is nothing to do
+    if std::ptr::eq(target, other) {
+        return true;
+    }
+    let (target, other) = (&mut (*target).0, &(*other).0);
+    if target.len() != other.len() {
+        return false;
+    }
+    op(target, other);
+    true
+}
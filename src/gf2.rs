@@ -0,0 +1,74 @@
+//! Polynomials over GF(2)
+//!
+//! A bitboard is seen as the polynomial whose coefficient of x^i is the bit i.
+
+use crate::BitboardInternal;
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Returns the degree of the bitboard seen as a polynomial, None for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.degree_below(N)
+    }
+
+    /// Returns the carry-less product of the two polynomials, the terms of degree BITS or more
+    /// are deleted so this is the product modulo x^BITS
+    pub fn clmul(&self, other: &Self) -> Self {
+        let mut factor = *other;
+        // junk bits are not part of the polynomial
+        factor.words[N - 1] &= Self::LAST_WORD_MASK;
+
+        let mut product = Self::new();
+        for shift in self.iter() {
+            product.xor_shifted(&factor, shift);
+        }
+        product
+    }
+
+    /// Returns the quotient and the remainder of the polynomial division by divisor
+    /// The divisor can't be zero
+    pub fn div_rem(&self, divisor: &Self) -> (Self, Self) {
+        let divisor_degree = divisor.degree().expect("polynomial division by zero");
+
+        let mut quotient = Self::new();
+        let mut remainder = *self;
+        // junk bits are not part of the polynomial
+        remainder.words[N - 1] &= Self::LAST_WORD_MASK;
+
+        // the degree of the remainder only decreases so the scan resumes from its last word
+        let mut words = N;
+        while let Some(degree) = remainder.degree_below(words) {
+            if degree < divisor_degree {
+                break;
+            }
+            let shift = degree - divisor_degree;
+            quotient.set(shift);
+            remainder.xor_shifted(divisor, shift);
+            words = degree / 64 + 1;
+        }
+        (quotient, remainder)
+    }
+
+    /// Returns the degree of the polynomial made of the first words, junk bits excluded
+    fn degree_below(&self, words: usize) -> Option<usize> {
+        (0..words).rev().find_map(|index| {
+            let word = if index == N - 1 { self.words[index] & Self::LAST_WORD_MASK } else { self.words[index] };
+            if word == 0 {
+                None
+            } else {
+                Some(index * 64 + 63 - word.leading_zeros() as usize)
+            }
+        })
+    }
+
+    /// Xors other shifted by the given amount towards the MSBs, the overflow is deleted
+    fn xor_shifted(&mut self, other: &Self, shift: usize) {
+        let (word_shift, bit_shift) = (shift / 64, shift % 64);
+        for (index, &word) in other.words.iter().enumerate().take(N - word_shift) {
+            self.words[index + word_shift] ^= word << bit_shift;
+            if bit_shift > 0 && index + word_shift + 1 < N {
+                self.words[index + word_shift + 1] ^= word >> (64 - bit_shift);
+            }
+        }
+        self.words[N - 1] &= Self::LAST_WORD_MASK;
+    }
+}
@@ -0,0 +1,424 @@
+//! GPU backend for huge bitboards, built on wgpu
+//!
+//! A GpuBitboard lives in GPU memory as 32 bits words (WGSL has no 64 bits integers), the bit
+//! i being the bit i % 32 of the word i / 32 as on the CPU. The operations are only recorded
+//! and submitted to the GPU queue: the CPU only waits for them at the sync points, download
+//! and count_ones, which read the results back.
+//!
+//! The boards are limited by the maximum storage buffer binding size of the adapter, and to
+//! MAX_BITS bits since the kernels index the cells with u32.
+
+use crate::automaton::Rule;
+use crate::{DynBitboard, Topology};
+use wgpu::util::DeviceExt;
+
+const WORKGROUP_SIZE: u32 = 64;
+// the maximum number of workgroups in a dispatch dimension
+const MAX_WORKGROUPS: u32 = 65535;
+
+/// The maximum number of bits of a GpuBitboard, the cell indices of its last word fit in a u32
+pub const MAX_BITS: usize = (u32::MAX - 31) as usize;
+
+/// The parameters of all the kernels, WORD_INDEX gives the word of an invocation
+const PARAMS: &str = "
+struct Params {
+    op: u32,
+    words: u32,
+    line_size: u32,
+    line_count: u32,
+    birth: u32,
+    survival: u32,
+    topology: u32,
+}
+@group(0) @binding(2) var<uniform> params: Params;
+
+fn word_index(id: vec3<u32>, groups: vec3<u32>) -> u32 {
+    return id.x + id.y * groups.x * 64u;
+}
+";
+
+const COMBINE_SHADER: &str = "
+@group(0) @binding(0) var<storage, read_write> target_words: array<u32>;
+@group(0) @binding(1) var<storage, read> other_words: array<u32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {
+    let i = word_index(id, groups);
+    if (i >= params.words) {
+        return;
+    }
+    let a = target_words[i];
+    let b = other_words[i];
+    switch params.op {
+        case 0u: { target_words[i] = a & b; }
+        case 1u: { target_words[i] = a | b; }
+        case 2u: { target_words[i] = a ^ b; }
+        default: { target_words[i] = a & ~b; }
+    }
+}
+";
+
+// every workgroup writes the count of its words, at most 2048, they are summed on the CPU
+const POPCOUNT_SHADER: &str = "
+@group(0) @binding(0) var<storage, read> words: array<u32>;
+@group(0) @binding(1) var<storage, read_write> partial_sums: array<u32>;
+
+var<workgroup> partial_sum: atomic<u32>;
+
+@compute @workgroup_size(64)
+fn main(
+    @builtin(global_invocation_id) id: vec3<u32>,
+    @builtin(num_workgroups) groups: vec3<u32>,
+    @builtin(workgroup_id) group: vec3<u32>,
+    @builtin(local_invocation_index) local: u32,
+) {
+    let i = word_index(id, groups);
+    if (i < params.words) {
+        atomicAdd(&partial_sum, countOneBits(words[i]));
+    }
+    workgroupBarrier();
+    if (local == 0u) {
+        partial_sums[group.x + group.y * groups.x] = atomicLoad(&partial_sum);
+    }
+}
+";
+
+// every invocation computes a whole output word, so there is no write conflict
+const AUTOMATON_SHADER: &str = "
+@group(0) @binding(0) var<storage, read_write> next_words: array<u32>;
+@group(0) @binding(1) var<storage, read> words: array<u32>;
+
+// the topologies as in GpuContext::automaton_step
+const TOROIDAL: u32 = 1u;
+const CYLINDRICAL: u32 = 2u;
+
+// returns the cell (x + dx - 1, y + dy - 1), dx and dy being 0, 1 or 2 so that the coordinates
+// stay unsigned, the cells out of the grid are dead unless the topology wraps them around
+fn alive(x: u32, y: u32, dx: u32, dy: u32) -> u32 {
+    let width = params.line_size;
+    let height = params.line_count;
+    // the coordinates plus one, 0 and width + 1 being out of the grid
+    var moved_x = x + dx;
+    var moved_y = y + dy;
+    if (params.topology == TOROIDAL || params.topology == CYLINDRICAL) {
+        if (moved_x == 0u) {
+            moved_x = width;
+        } else if (moved_x == width + 1u) {
+            moved_x = 1u;
+        }
+    }
+    if (params.topology == TOROIDAL) {
+        if (moved_y == 0u) {
+            moved_y = height;
+        } else if (moved_y == height + 1u) {
+            moved_y = 1u;
+        }
+    }
+    if (moved_x == 0u || moved_y == 0u || moved_x > width || moved_y > height) {
+        return 0u;
+    }
+    let cell = (moved_y - 1u) * width + moved_x - 1u;
+    return (words[cell / 32u] >> (cell % 32u)) & 1u;
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {
+    let i = word_index(id, groups);
+    if (i >= params.words) {
+        return;
+    }
+    let cells = params.line_size * params.line_count;
+    var word = 0u;
+    for (var bit = 0u; bit < 32u; bit++) {
+        let cell = i * 32u + bit;
+        if (cell >= cells) {
+            break;
+        }
+        let x = cell % params.line_size;
+        let y = cell / params.line_size;
+        var count = 0u;
+        for (var dy = 0u; dy <= 2u; dy++) {
+            for (var dx = 0u; dx <= 2u; dx++) {
+                if (dx != 1u || dy != 1u) {
+                    count += alive(x, y, dx, dy);
+                }
+            }
+        }
+        var rule = params.birth;
+        if (alive(x, y, 1u, 1u) == 1u) {
+            rule = params.survival;
+        }
+        word |= ((rule >> count) & 1u) << bit;
+    }
+    next_words[i] = word;
+}
+";
+
+/// The word by word operations of GpuContext::combine
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GpuOp {
+    And,
+    Or,
+    Xor,
+    AndNot,
+}
+
+/// A bitboard stored in GPU memory, see GpuContext::upload
+pub struct GpuBitboard {
+    buffer: wgpu::Buffer,
+    len: usize,
+}
+
+impl GpuBitboard {
+    /// Returns the number of bits of the bitboard
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns wether the bitboard has no bits
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn word_count(&self) -> u32 {
+        word_count(self.len)
+    }
+}
+
+/// A GPU device along with the compiled kernels
+pub struct GpuContext {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    combine: wgpu::ComputePipeline,
+    popcount: wgpu::ComputePipeline,
+    automaton: wgpu::ComputePipeline,
+}
+
+impl GpuContext {
+    /// Opens the default GPU with its maximum limits, None if there is no usable adapter
+    pub fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::new_without_display_handle());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .ok()?;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("bitboards"),
+            required_limits: adapter.limits(),
+            ..Default::default()
+        }))
+        .ok()?;
+
+        let pipeline = |source: &str| {
+            let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(format!("{}{}", PARAMS, source).into()),
+            });
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: None,
+                module: &module,
+                entry_point: Some("main"),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        Some(Self {
+            combine: pipeline(COMBINE_SHADER),
+            popcount: pipeline(POPCOUNT_SHADER),
+            automaton: pipeline(AUTOMATON_SHADER),
+            device,
+            queue,
+        })
+    }
+
+    /// Copies a bitboard to the GPU, it can't have more than MAX_BITS bits
+    pub fn upload(&self, board: &DynBitboard) -> GpuBitboard {
+        assert!(board.len() <= MAX_BITS, "The bitboard is too large for the GPU kernels");
+        let mut bytes = Vec::with_capacity(word_count(board.len()) as usize * 4);
+        for start in (0..board.len()).step_by(32) {
+            let width = 32.min(board.len() - start);
+            bytes.extend_from_slice(&(board.get_field(start, width) as u32).to_le_bytes());
+        }
+        // wgpu doesn't allow empty buffers
+        bytes.resize(bytes.len().max(4), 0);
+
+        let buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents: &bytes,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+        });
+        GpuBitboard {
+            buffer,
+            len: board.len(),
+        }
+    }
+
+    /// Waits for the pending operations on the bitboard and copies it back, this is a sync
+    /// point
+    pub fn download(&self, board: &GpuBitboard) -> DynBitboard {
+        let bytes = self.read_back(&board.buffer);
+        let mut result = DynBitboard::new(board.len);
+        for (index, chunk) in bytes.chunks_exact(4).enumerate().take(board.word_count() as usize) {
+            let word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            let start = index * 32;
+            result.set_field(start, 32.min(board.len - start), word as u64);
+        }
+        result
+    }
+
+    /// Combines target word by word with other, both have to be of the same size
+    pub fn combine(&self, target: &mut GpuBitboard, other: &GpuBitboard, op: GpuOp) {
+        assert_eq!(target.len, other.len, "The bitboards have different sizes");
+        let op = match op {
+            GpuOp::And => 0,
+            GpuOp::Or => 1,
+            GpuOp::Xor => 2,
+            GpuOp::AndNot => 3,
+        };
+        let params = self.params([op, target.word_count(), 0, 0, 0, 0, 0]);
+        self.run(&self.combine, &[&target.buffer, &other.buffer, &params], target.word_count());
+    }
+
+    /// Returns the number of set bits, this is a sync point
+    /// Every workgroup counts its words on the GPU and their counts are summed on the CPU
+    pub fn count_ones(&self, board: &GpuBitboard) -> usize {
+        let (groups_x, groups_y) = dispatch_size(board.word_count());
+        let partial_sums = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: u64::from(groups_x) * u64::from(groups_y) * 4,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let params = self.params([0, board.word_count(), 0, 0, 0, 0, 0]);
+        self.run(&self.popcount, &[&board.buffer, &partial_sums, &params], board.word_count());
+
+        let bytes = self.read_back(&partial_sums);
+        let count: u64 = bytes
+            .chunks_exact(4)
+            .map(|chunk| u64::from(u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]])))
+            .sum();
+        count as usize
+    }
+
+    /// Returns the next generation of the board seen as lines of line_size cells under the
+    /// rule, the cells going out of the grid being dead or wrapping around depending on the
+    /// topology as in a Grid
+    pub fn automaton_step(
+        &self,
+        board: &GpuBitboard,
+        line_size: usize,
+        topology: Topology,
+        rule: &Rule,
+    ) -> GpuBitboard {
+        assert!(line_size > 0);
+        let topology = match topology {
+            Topology::Bounded => 0,
+            Topology::Toroidal => 1,
+            Topology::Cylindrical => 2,
+        };
+        let line_count = board.len / line_size;
+        let next = GpuBitboard {
+            buffer: self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: board.buffer.size(),
+                usage: board.buffer.usage(),
+                mapped_at_creation: false,
+            }),
+            len: board.len,
+        };
+        let params = self.params([
+            0,
+            board.word_count(),
+            line_size as u32,
+            line_count as u32,
+            rule.birth as u32,
+            rule.survival as u32,
+            topology,
+        ]);
+        self.run(&self.automaton, &[&next.buffer, &board.buffer, &params], board.word_count());
+        next
+    }
+
+    /// Returns the uniform buffer of the kernel parameters
+    fn params(&self, values: [u32; 7]) -> wgpu::Buffer {
+        // padded to 32 bytes
+        let mut bytes = [0; 32];
+        for (chunk, value) in bytes.chunks_exact_mut(4).zip(values.iter()) {
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents: &bytes,
+            usage: wgpu::BufferUsages::UNIFORM,
+        })
+    }
+
+    /// Submits a kernel with the buffers bound in order, with an invocation per word
+    fn run(&self, pipeline: &wgpu::ComputePipeline, buffers: &[&wgpu::Buffer], words: u32) {
+        let layout = pipeline.get_bind_group_layout(0);
+        let entries: Vec<_> = buffers
+            .iter()
+            .enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect();
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &layout,
+            entries: &entries,
+        });
+
+        let (groups_x, groups_y) = dispatch_size(words);
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(groups_x, groups_y, 1);
+        }
+        self.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Waits for the GPU and returns the content of a buffer
+    fn read_back(&self, buffer: &wgpu::Buffer) -> Vec<u8> {
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: buffer.size(),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, buffer.size());
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = staging.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| result.expect("Failed to map the buffer"));
+        self.device
+            .poll(wgpu::PollType::Wait {
+                submission_index: None,
+                timeout: None,
+            })
+            .expect("Failed to wait for the GPU");
+        let bytes = slice.get_mapped_range().expect("Failed to read the buffer").to_vec();
+        staging.unmap();
+        bytes
+    }
+}
+
+/// Returns the number of workgroups in both dimensions for an invocation per word, they are
+/// spread over two dimensions past the limit of one
+fn dispatch_size(words: u32) -> (u32, u32) {
+    let groups = words.div_ceil(WORKGROUP_SIZE).max(1);
+    (groups.min(MAX_WORKGROUPS), groups.div_ceil(MAX_WORKGROUPS))
+}
+
+/// Returns the number of 32 bits words of len bits
+fn word_count(len: usize) -> u32 {
+    len.div_ceil(32) as u32
+}
@@ -0,0 +1,121 @@
+//! Boards as adjacency matrices
+//!
+//! A graph of n nodes is stored as a n x n grid: the bit i * n + j is set if there is an edge
+//! from the node i to the node j.
+
+use crate::BitboardInternal;
+use std::fmt::Write;
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Returns the directed graph in the Graphviz DOT language, with a node per label
+    pub fn to_dot(&self, node_labels: &[&str]) -> String {
+        let node_count = node_labels.len();
+        assert!(node_count * node_count <= Self::BITS, "Too many nodes for the bitboard");
+
+        let mut dot = String::from("digraph {\n");
+        for (node, label) in node_labels.iter().enumerate() {
+            let label = label.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(dot, "    {} [label=\"{}\"];", node, label).unwrap();
+        }
+        for from in 0..node_count {
+            for to in 0..node_count {
+                if self.is_set(from * node_count + to) {
+                    writeln!(dot, "    {} -> {};", from, to).unwrap();
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Conversions from and to petgraph, the nodes are numbered as in the adjacency matrix
+#[cfg(feature = "petgraph")]
+mod petgraph_support {
+    use crate::BitboardInternal;
+    use petgraph::graph::{DiGraph, Graph, IndexType};
+    use petgraph::graphmap::{DiGraphMap, GraphMap};
+    use petgraph::visit::{EdgeRef, VisitMap};
+    use petgraph::EdgeType;
+
+    impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+        /// Returns the directed graph of node_count nodes with the edges of the matrix
+        pub fn to_graph(&self, node_count: usize) -> DiGraph<(), ()> {
+            assert!(node_count * node_count <= Self::BITS, "Too many nodes for the bitboard");
+
+            let mut graph = DiGraph::with_capacity(node_count, 0);
+            let nodes: Vec<_> = (0..node_count).map(|_| graph.add_node(())).collect();
+            for index in self.set_indices().take_while(|&index| index < node_count * node_count) {
+                graph.add_edge(nodes[index / node_count], nodes[index % node_count], ());
+            }
+            graph
+        }
+
+        /// Returns the adjacency matrix of the graph, both directions are set for the edges of
+        /// an undirected graph
+        pub fn from_graph<V, E, Ty: EdgeType, Ix: IndexType>(graph: &Graph<V, E, Ty, Ix>) -> Self {
+            let node_count = graph.node_count();
+            assert!(node_count * node_count <= Self::BITS, "Too many nodes for the bitboard");
+
+            let mut matrix = Self::new();
+            for edge in graph.edge_references() {
+                let (from, to) = (edge.source().index(), edge.target().index());
+                matrix.set(from * node_count + to);
+                if !graph.is_directed() {
+                    matrix.set(to * node_count + from);
+                }
+            }
+            matrix
+        }
+
+        /// Returns the directed graph map with the nodes 0 to node_count and the edges of the
+        /// matrix
+        pub fn to_graph_map(&self, node_count: usize) -> DiGraphMap<usize, ()> {
+            assert!(node_count * node_count <= Self::BITS, "Too many nodes for the bitboard");
+
+            let mut graph = DiGraphMap::with_capacity(node_count, 0);
+            for node in 0..node_count {
+                graph.add_node(node);
+            }
+            for index in self.set_indices().take_while(|&index| index < node_count * node_count) {
+                graph.add_edge(index / node_count, index % node_count, ());
+            }
+            graph
+        }
+
+        /// Returns the adjacency matrix of node_count nodes of a graph map over the nodes 0 to
+        /// node_count, both directions are set for the edges of an undirected graph
+        pub fn from_graph_map<E, Ty: EdgeType>(graph: &GraphMap<usize, E, Ty>, node_count: usize) -> Self {
+            assert!(node_count * node_count <= Self::BITS, "Too many nodes for the bitboard");
+
+            let mut matrix = Self::new();
+            for (from, to, _) in graph.all_edges() {
+                assert!(from < node_count && to < node_count, "Node out of the matrix");
+                matrix.set(from * node_count + to);
+                if !graph.is_directed() {
+                    matrix.set(to * node_count + from);
+                }
+            }
+            matrix
+        }
+    }
+
+    /// A bitboard can hold the visited nodes of a petgraph traversal
+    impl<const N: usize, const R: usize, Ix: IndexType> VisitMap<Ix> for BitboardInternal<N, R> {
+        fn visit(&mut self, node: Ix) -> bool {
+            let first_visit = self.is_unset(node.index());
+            self.set(node.index());
+            first_visit
+        }
+
+        fn is_visited(&self, node: &Ix) -> bool {
+            self.is_set(node.index())
+        }
+
+        fn unvisit(&mut self, node: Ix) -> bool {
+            let visited = self.is_set(node.index());
+            self.unset(node.index());
+            visited
+        }
+    }
+}
@@ -0,0 +1,659 @@
+//! 2D helpers
+//!
+//! As in print_by_line, the bitboard is seen as a grid of lines of line_size bits, the cell
+//! (x, y) being the bit y * line_size + x. A last incomplete line is ignored.
+//!
+//! The operations moving cells around are done by a Grid, which holds the masks preventing
+//! the bits from wrapping around the lines. North is towards the line 0 and west towards the
+//! column 0. The Topology of the grid tells what happens to the cells going out of it.
+
+use crate::{BitIndex, BitboardInternal, Coord};
+
+/// An axis aligned rectangle of cells, (x, y) being its corner with the smallest coordinates
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Rectangle {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rectangle {
+    /// Returns the number of cells of the rectangle
+    #[inline]
+    pub fn area(&self) -> usize {
+        self.width * self.height
+    }
+}
+
+/// Aggregates over the set cells of a grid, see BitboardInternal::stats
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoardStats {
+    /// The number of set cells
+    pub count: usize,
+    /// The proportion of set cells in the grid, 0 if it has no complete line
+    pub density: f64,
+    /// The smallest rectangle holding the set cells, None if there is none
+    pub bounding_box: Option<Rectangle>,
+    /// The mean (x, y) of the set cells, None if there is none
+    pub centroid: Option<(f64, f64)>,
+    /// The set cells in the north west, north east, south west and south east quadrants, the
+    /// west ones having x < line_size / 2 and the north ones y < line_count / 2
+    pub quadrant_counts: [usize; 4],
+}
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Returns the number of complete lines of the grid
+    #[inline]
+    pub fn line_count(line_size: usize) -> usize {
+        assert!(line_size > 0);
+        Self::BITS / line_size
+    }
+
+    /// Calls f with the words of the given line, from left to right, along with the number
+    /// of cells in each word
+    pub fn for_each_line_word<F: FnMut(u64, usize)>(&self, line_no: usize, line_size: usize, mut f: F) {
+        let start = line_no * line_size;
+        let mut x = 0;
+        while x < line_size {
+            let width = 64.min(line_size - x);
+            f(self.get_field(start + x, width), width);
+            x += width;
+        }
+    }
+
+    /// Returns an iterator over the coordinates of the set cells, line by line
+    pub fn ones_coords(&self, line_size: usize) -> impl Iterator<Item = Coord> + '_ {
+        let cells = Self::line_count(line_size) * line_size;
+        self.set_indices()
+            .take_while(move |&index| index < cells)
+            .map(move |index| Coord::from_flat(index, line_size))
+    }
+
+    /// Returns the statistics of the set cells, computed in a single pass over them
+    pub fn stats(&self, line_size: usize) -> BoardStats {
+        let line_count = Self::line_count(line_size);
+        let (half_width, half_height) = (line_size / 2, line_count / 2);
+
+        let mut count = 0;
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (usize::MAX, usize::MAX, 0, 0);
+        let (mut sum_x, mut sum_y) = (0, 0);
+        let mut quadrant_counts = [0; 4];
+        for Coord(x, y) in self.ones_coords(line_size) {
+            count += 1;
+            min_x = min_x.min(x);
+            max_x = max_x.max(x);
+            // the cells come line by line
+            min_y = min_y.min(y);
+            max_y = y;
+            sum_x += x;
+            sum_y += y;
+            quadrant_counts[(y >= half_height) as usize * 2 + (x >= half_width) as usize] += 1;
+        }
+
+        // no complete line when line_size is larger than the bitboard
+        let cells = line_count * line_size;
+        let density = if cells == 0 { 0.0 } else { count as f64 / cells as f64 };
+        let (bounding_box, centroid) = if count == 0 {
+            (None, None)
+        } else {
+            let rectangle = Rectangle {
+                x: min_x,
+                y: min_y,
+                width: max_x - min_x + 1,
+                height: max_y - min_y + 1,
+            };
+            let centroid = (sum_x as f64 / count as f64, sum_y as f64 / count as f64);
+            (Some(rectangle), Some(centroid))
+        };
+        BoardStats {
+            count,
+            density,
+            bounding_box,
+            centroid,
+            quadrant_counts,
+        }
+    }
+
+    /// Returns the largest rectangle made only of set cells, None if the bitboard is empty
+    /// This is the classic row histogram algorithm, in O(cells)
+    pub fn largest_rectangle_of_ones(&self, line_size: usize) -> Option<Rectangle> {
+        let mut heights = vec![0; line_size];
+        let mut best: Option<Rectangle> = None;
+        // start column and height of the bars that can still be extended to the right
+        let mut stack: Vec<(usize, usize)> = Vec::with_capacity(line_size);
+
+        for y in 0..Self::line_count(line_size) {
+            let mut x = 0;
+            self.for_each_line_word(y, line_size, |word, width| {
+                for bit in 0..width {
+                    let height = &mut heights[x + bit];
+                    *height = if word & (1 << bit) != 0 { *height + 1 } else { 0 };
+                }
+                x += width;
+            });
+
+            stack.clear();
+            // a last bar of height 0 flushes the stack
+            for x in 0..=line_size {
+                let height = heights.get(x).copied().unwrap_or(0);
+                let mut start = x;
+                while let Some(&(bar_start, bar_height)) = stack.last() {
+                    if bar_height < height {
+                        break;
+                    }
+                    stack.pop();
+                    let candidate = Rectangle {
+                        x: bar_start,
+                        y: y + 1 - bar_height,
+                        width: x - bar_start,
+                        height: bar_height,
+                    };
+                    if bar_height > 0 && best.is_none_or(|best| candidate.area() > best.area()) {
+                        best = Some(candidate);
+                    }
+                    start = bar_start;
+                }
+                stack.push((start, height));
+            }
+        }
+        best
+    }
+}
+
+/// The directions in which cells can be moved
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl Direction {
+    /// All the directions, the orthogonal ones first
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::South,
+        Direction::East,
+        Direction::West,
+        Direction::NorthEast,
+        Direction::NorthWest,
+        Direction::SouthEast,
+        Direction::SouthWest,
+    ];
+
+    /// Returns the (dx, dy) of a step in the direction
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::North => (0, -1),
+            Direction::South => (0, 1),
+            Direction::East => (1, 0),
+            Direction::West => (-1, 0),
+            Direction::NorthEast => (1, -1),
+            Direction::NorthWest => (-1, -1),
+            Direction::SouthEast => (1, 1),
+            Direction::SouthWest => (-1, 1),
+        }
+    }
+}
+
+/// The neighbors of a cell, Four for the orthogonal ones and Eight for the diagonal ones too
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+/// What is beyond the edges of a grid
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Topology {
+    /// Nothing, the cells going out of the grid are lost
+    Bounded,
+    /// The lines and the columns wrap around
+    Toroidal,
+    /// The lines wrap around (x wraps) but not the columns
+    Cylindrical,
+}
+
+impl Topology {
+    #[inline]
+    fn wraps_x(self) -> bool {
+        self != Topology::Bounded
+    }
+
+    #[inline]
+    fn wraps_y(self) -> bool {
+        self == Topology::Toroidal
+    }
+}
+
+/// The geometry of a grid, see the module documentation
+#[derive(Clone)]
+pub struct Grid<const N: usize, const R: usize> {
+    line_size: usize,
+    topology: Topology,
+    // all the cells of the complete lines
+    cells: BitboardInternal<N, R>,
+    not_first_column: BitboardInternal<N, R>,
+    not_last_column: BitboardInternal<N, R>,
+}
+
+impl<const N: usize, const R: usize> Grid<N, R> {
+    /// Creates the bounded grid of lines of line_size cells
+    pub fn new(line_size: usize) -> Self {
+        Self::with_topology(line_size, Topology::Bounded)
+    }
+
+    /// Creates the grid of lines of line_size cells with the given topology
+    pub fn with_topology(line_size: usize, topology: Topology) -> Self {
+        let line_count = BitboardInternal::<N, R>::line_count(line_size);
+
+        let mut cells = BitboardInternal::new();
+        let mut first_column = BitboardInternal::new();
+        let mut last_column = BitboardInternal::new();
+        for y in 0..line_count {
+            for x in (0..line_size).step_by(64) {
+                let width = 64.min(line_size - x);
+                cells.set_field(y * line_size + x, width, !0);
+            }
+            first_column.set(y * line_size);
+            last_column.set(y * line_size + line_size - 1);
+        }
+
+        let mut not_first_column = cells;
+        not_first_column -= first_column;
+        let mut not_last_column = cells;
+        not_last_column -= last_column;
+
+        Self {
+            line_size,
+            topology,
+            cells,
+            not_first_column,
+            not_last_column,
+        }
+    }
+
+    /// Returns the number of cells in a line
+    #[inline]
+    pub fn line_size(&self) -> usize {
+        self.line_size
+    }
+
+    /// Returns the topology of the grid
+    #[inline]
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Returns the number of lines
+    #[inline]
+    pub fn line_count(&self) -> usize {
+        BitboardInternal::<N, R>::line_count(self.line_size)
+    }
+
+    /// Returns the bitboard with all the cells of the grid set
+    #[inline]
+    pub fn cells(&self) -> &BitboardInternal<N, R> {
+        &self.cells
+    }
+
+    /// Returns the index of the cell (x, y)
+    #[inline]
+    pub fn index(&self, x: usize, y: usize) -> usize {
+        debug_assert!(x < self.line_size && y < self.line_count());
+        y * self.line_size + x
+    }
+
+    /// Returns the coordinates of the cell with the given index
+    #[inline]
+    pub fn coords(&self, index: usize) -> (usize, usize) {
+        (index % self.line_size, index / self.line_size)
+    }
+
+    /// Returns the index of the neighbor of a cell in the given direction, None if it is out
+    /// of the grid
+    pub fn neighbor(&self, index: usize, direction: Direction) -> Option<usize> {
+        let (x, y) = self.coords(index);
+        let (dx, dy) = direction.offset();
+        let step = |coordinate: usize, delta: isize, size: usize, wraps: bool| {
+            let moved = coordinate as isize + delta;
+            if moved >= 0 && moved < size as isize {
+                Some(moved as usize)
+            } else if wraps {
+                Some(moved.rem_euclid(size as isize) as usize)
+            } else {
+                None
+            }
+        };
+        let x = step(x, dx, self.line_size, self.topology.wraps_x())?;
+        let y = step(y, dy, self.line_count(), self.topology.wraps_y())?;
+        Some(self.index(x, y))
+    }
+
+    /// Moves every cell of the board by one step in the given direction, the cells going out
+    /// of the grid are lost or wrap around depending on the topology
+    pub fn shift(&self, board: &BitboardInternal<N, R>, direction: Direction) -> BitboardInternal<N, R> {
+        let mut result = *board;
+        result &= &self.cells;
+        // the cells crossing the edge, moved to the opposite side
+        let mut wrapped = result;
+        let last_line_start = (self.line_count().max(1) - 1) * self.line_size;
+        match direction {
+            Direction::North => {
+                result.shift_right_by(self.line_size);
+                wrapped.shift_left_by(last_line_start);
+            }
+            Direction::South => {
+                result.shift_left_by(self.line_size);
+                wrapped.shift_right_by(last_line_start);
+            }
+            Direction::East => {
+                result &= &self.not_last_column;
+                result.shift_left_one();
+                wrapped -= &self.not_last_column;
+                wrapped.shift_right_by(self.line_size - 1);
+            }
+            Direction::West => {
+                result &= &self.not_first_column;
+                result.shift_right_by(1);
+                wrapped -= &self.not_first_column;
+                wrapped.shift_left_by(self.line_size - 1);
+            }
+            Direction::NorthEast => return self.shift(&self.shift(board, Direction::East), Direction::North),
+            Direction::NorthWest => return self.shift(&self.shift(board, Direction::West), Direction::North),
+            Direction::SouthEast => return self.shift(&self.shift(board, Direction::East), Direction::South),
+            Direction::SouthWest => return self.shift(&self.shift(board, Direction::West), Direction::South),
+        }
+        let wraps = match direction {
+            Direction::North | Direction::South => self.topology.wraps_y(),
+            _ => self.topology.wraps_x(),
+        };
+        if wraps {
+            result |= &wrapped;
+        }
+        result &= &self.cells;
+        result
+    }
+
+    /// Returns the board with the neighbors of all its cells added
+    pub fn dilate(&self, board: &BitboardInternal<N, R>, connectivity: Connectivity) -> BitboardInternal<N, R> {
+        let mut board = *board;
+        board &= &self.cells;
+
+        let mut horizontal = board;
+        horizontal |= &self.shift(&board, Direction::East);
+        horizontal |= &self.shift(&board, Direction::West);
+
+        // the 8 neighbors are the vertical neighbors of the horizontal ones
+        let vertical_source = match connectivity {
+            Connectivity::Four => &board,
+            Connectivity::Eight => &horizontal,
+        };
+        let mut result = self.shift(vertical_source, Direction::North);
+        result |= &self.shift(vertical_source, Direction::South);
+        result |= &horizontal;
+        result
+    }
+
+    /// Returns an iterator over the successive BFS frontiers from start through the passable
+    /// cells: the start itself, then the cells at distance 1, 2 and so on
+    /// The iteration stops when no new cell can be reached
+    pub fn frontier_layers<'a>(
+        &'a self,
+        start: &BitboardInternal<N, R>,
+        passable: &'a BitboardInternal<N, R>,
+        connectivity: Connectivity,
+    ) -> FrontierLayers<'a, N, R> {
+        FrontierLayers {
+            grid: self,
+            passable,
+            connectivity,
+            frontier: Some(*start),
+            visited: *start,
+        }
+    }
+}
+
+impl<const N: usize, const R: usize> Grid<N, R> {
+    /// Returns a shortest path from start to goal avoiding the obstacles, as the bitboard of
+    /// its cells (start and goal included) along with its number of steps
+    /// start and goal are Coord or flat indices, see the index module
+    /// Returns None if the goal can't be reached
+    /// Only a fixed number of boards is kept whatever the length of the path
+    pub fn find_path<I: BitIndex>(
+        &self,
+        obstacles: &BitboardInternal<N, R>,
+        start: I,
+        goal: I,
+        connectivity: Connectivity,
+    ) -> Option<(BitboardInternal<N, R>, usize)> {
+        let (start, goal) = (start.to_flat(self.line_size), goal.to_flat(self.line_size));
+        let mut passable = self.cells;
+        passable -= obstacles;
+        if passable.is_unset(start) || passable.is_unset(goal) {
+            return None;
+        }
+
+        let mut start_board = BitboardInternal::new();
+        start_board.set(start);
+
+        // the reached cells by distance from the start modulo 3, the neighbors of a cell at
+        // distance d are at distance d - 1, d or d + 1 so this tells which ones are closer
+        let mut distance_classes = [BitboardInternal::<N, R>::new(); 3];
+        let mut length = None;
+        for (distance, layer) in self.frontier_layers(&start_board, &passable, connectivity).enumerate() {
+            distance_classes[distance % 3] |= &layer;
+            if layer.is_set(goal) {
+                length = Some(distance);
+                break;
+            }
+        }
+        let length = length?;
+
+        let mut path = BitboardInternal::new();
+        let mut current = goal;
+        path.set(current);
+        for distance in (0..length).rev() {
+            let mut cell = BitboardInternal::new();
+            cell.set(current);
+            let mut previous = self.dilate(&cell, connectivity);
+            previous &= &distance_classes[distance % 3];
+            current = previous.set_indices().next().unwrap();
+            path.set(current);
+        }
+        Some((path, length))
+    }
+}
+
+/// How to share the cells reached by several seeds at the same distance, see Grid::territories
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Ties {
+    /// The cell belongs to all the seeds reaching it
+    Shared,
+    /// The cell belongs to none of them and stops their expansion
+    Neutral,
+    /// The cell belongs to the seed that comes first in the slice
+    FirstSeed,
+}
+
+impl<const N: usize, const R: usize> Grid<N, R> {
+    /// Runs a BFS from all the seeds at once through the passable cells and returns for each
+    /// seed the cells it reaches before the others
+    pub fn territories(
+        &self,
+        seeds: &[BitboardInternal<N, R>],
+        passable: &BitboardInternal<N, R>,
+        connectivity: Connectivity,
+        ties: Ties,
+    ) -> Vec<BitboardInternal<N, R>> {
+        let mut passable = *passable;
+        passable &= &self.cells;
+
+        let mut frontiers: Vec<_> = seeds
+            .iter()
+            .map(|seed| {
+                let mut frontier = *seed;
+                frontier &= &passable;
+                frontier
+            })
+            .collect();
+        let mut territories = vec![BitboardInternal::new(); seeds.len()];
+        let mut claimed = BitboardInternal::<N, R>::new();
+
+        while frontiers.iter().any(|frontier| !frontier.is_empty()) {
+            // the cells of the previous frontiers, and the ones reached by at least two of them
+            let mut reached = BitboardInternal::<N, R>::new();
+            let mut contested = BitboardInternal::<N, R>::new();
+            for frontier in frontiers.iter_mut() {
+                if ties == Ties::FirstSeed {
+                    *frontier -= &reached;
+                }
+                let mut overlap = *frontier;
+                overlap &= &reached;
+                contested |= &overlap;
+                reached |= &*frontier;
+            }
+            if ties == Ties::Neutral {
+                for frontier in frontiers.iter_mut() {
+                    *frontier -= &contested;
+                }
+            }
+            claimed |= &reached;
+
+            for (frontier, territory) in frontiers.iter_mut().zip(territories.iter_mut()) {
+                *territory |= &*frontier;
+                let mut next = self.dilate(frontier, connectivity);
+                next &= &passable;
+                next -= &claimed;
+                *frontier = next;
+            }
+        }
+        territories
+    }
+}
+
+/// A row of cells scanned by the shadowcasting, slopes are fractions (numerator, denominator)
+/// with a positive denominator
+struct ShadowRow {
+    depth: i64,
+    start_slope: (i64, i64),
+    end_slope: (i64, i64),
+}
+
+impl<const N: usize, const R: usize> Grid<N, R> {
+    /// Returns the cells visible from the origin, the blockers stop the sight but are visible
+    /// themselves when lit
+    /// The sight doesn't wrap around whatever the topology of the grid
+    /// This is the symmetric shadowcasting: a floor cell sees another one if and only if the
+    /// latter sees the former
+    /// origin is a Coord or a flat index, see the index module
+    pub fn field_of_view<I: BitIndex>(&self, blockers: &BitboardInternal<N, R>, origin: I) -> BitboardInternal<N, R> {
+        let origin = self.flat(origin);
+        let (origin_x, origin_y) = self.coords(origin);
+        let (origin_x, origin_y) = (origin_x as i64, origin_y as i64);
+        let (width, height) = (self.line_size as i64, self.line_count() as i64);
+
+        let mut visible = BitboardInternal::new();
+        visible.set(origin);
+
+        // (row, column) in a quadrant to grid (dx, dy) as dx = a*row + b*column, dy = c*row + d*column
+        // for north, south, east and west
+        let quadrants = [(0, 1, -1, 0), (0, 1, 1, 0), (1, 0, 0, 1), (-1, 0, 0, 1)];
+        for &(a, b, c, d) in quadrants.iter() {
+            // Returns the index of a quadrant cell, None if it is out of the grid
+            let cell = |row: i64, column: i64| {
+                let (x, y) = (origin_x + a * row + b * column, origin_y + c * row + d * column);
+                if x >= 0 && x < width && y >= 0 && y < height {
+                    Some((y * width + x) as usize)
+                } else {
+                    None
+                }
+            };
+            // the cells out of the grid are walls
+            let is_wall = |row: i64, column: i64| cell(row, column).is_none_or(|index| blockers.is_set(index));
+
+            let mut rows = vec![ShadowRow {
+                depth: 1,
+                start_slope: (-1, 1),
+                end_slope: (1, 1),
+            }];
+            while let Some(mut row) = rows.pop() {
+                let depth = row.depth;
+                let (start_numerator, start_denominator) = row.start_slope;
+                let (end_numerator, end_denominator) = row.end_slope;
+                // columns of depth * start_slope rounded half up to depth * end_slope rounded
+                // half down
+                let min_column = (2 * depth * start_numerator + start_denominator).div_euclid(2 * start_denominator);
+                let max_column = -(-(2 * depth * end_numerator - end_denominator)).div_euclid(2 * end_denominator);
+
+                let mut previous_wall = None;
+                for column in min_column..=max_column {
+                    let wall = is_wall(depth, column);
+                    let symmetric = column * start_denominator >= depth * start_numerator
+                        && column * end_denominator <= depth * end_numerator;
+                    if wall || symmetric {
+                        if let Some(index) = cell(depth, column) {
+                            visible.set(index);
+                        }
+                    }
+
+                    // the slope of the left edge of the cell
+                    let slope = (2 * column - 1, 2 * depth);
+                    if previous_wall == Some(true) && !wall {
+                        row.start_slope = slope;
+                    }
+                    if previous_wall == Some(false) && wall {
+                        rows.push(ShadowRow {
+                            depth: depth + 1,
+                            start_slope: row.start_slope,
+                            end_slope: slope,
+                        });
+                    }
+                    previous_wall = Some(wall);
+                }
+                if previous_wall == Some(false) {
+                    rows.push(ShadowRow {
+                        depth: depth + 1,
+                        start_slope: row.start_slope,
+                        end_slope: row.end_slope,
+                    });
+                }
+            }
+        }
+        visible
+    }
+}
+
+/// Iterator over the BFS frontiers of a start set, see Grid::frontier_layers
+pub struct FrontierLayers<'a, const N: usize, const R: usize> {
+    grid: &'a Grid<N, R>,
+    passable: &'a BitboardInternal<N, R>,
+    connectivity: Connectivity,
+    frontier: Option<BitboardInternal<N, R>>,
+    visited: BitboardInternal<N, R>,
+}
+
+impl<'a, const N: usize, const R: usize> Iterator for FrontierLayers<'a, N, R> {
+    type Item = BitboardInternal<N, R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frontier = self.frontier.take()?;
+        if frontier.is_empty() {
+            return None;
+        }
+
+        let mut next = self.grid.dilate(&frontier, self.connectivity);
+        next &= self.passable;
+        next -= &self.visited;
+        self.visited |= &next;
+        self.frontier = Some(next);
+
+        Some(frontier)
+    }
+}
@@ -0,0 +1,67 @@
+//! Hexadecimal strings of bitboards
+//!
+//! The bitboard is written as a big integer, the most significant digit first, with exactly
+//! BITS / 4 digits rounded up. A string of another length or with bits past BITS in its first
+//! digit is rejected so the round trip is exact.
+
+use crate::BitboardInternal;
+use std::fmt;
+
+/// The ways a hexadecimal string can fail to be parsed as a bitboard
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseHexError {
+    /// The string doesn't have the number of digits of the bitboard
+    Length { len: usize, expected: usize },
+    /// The character at the given byte offset is not an hexadecimal digit
+    InvalidDigit { offset: usize },
+    /// The first digit has bits past the size of the bitboard
+    JunkBits,
+}
+
+impl fmt::Display for ParseHexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Length { len, expected } => write!(f, "{} hexadecimal digits instead of {}", len, expected),
+            Self::InvalidDigit { offset } => write!(f, "invalid hexadecimal digit at {}", offset),
+            Self::JunkBits => write!(f, "the first digit has bits past the size of the bitboard"),
+        }
+    }
+}
+
+impl std::error::Error for ParseHexError {}
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// The number of hexadecimal digits of the bitboard
+    const HEX_DIGITS: usize = Self::BITS.div_ceil(4);
+
+    /// Returns the bitboard as lowercase hexadecimal digits, the most significant first
+    pub fn to_hex(&self) -> String {
+        (0..Self::HEX_DIGITS)
+            .rev()
+            .map(|digit| {
+                let start = digit * 4;
+                let value = self.get_field(start, 4.min(Self::BITS - start));
+                std::char::from_digit(value as u32, 16).unwrap()
+            })
+            .collect()
+    }
+
+    /// Parses the hexadecimal digits of to_hex, in lowercase or uppercase
+    pub fn from_hex(s: &str) -> Result<Self, ParseHexError> {
+        let len = s.len();
+        if len != Self::HEX_DIGITS {
+            return Err(ParseHexError::Length { len, expected: Self::HEX_DIGITS });
+        }
+        let mut board = Self::new();
+        for (offset, c) in s.char_indices() {
+            let value = c.to_digit(16).ok_or(ParseHexError::InvalidDigit { offset })? as u64;
+            let start = (Self::HEX_DIGITS - 1 - offset) * 4;
+            let width = 4.min(Self::BITS - start);
+            if value >> width != 0 {
+                return Err(ParseHexError::JunkBits);
+            }
+            board.set_field(start, width, value);
+        }
+        Ok(board)
+    }
+}
@@ -0,0 +1,224 @@
+//! Successive states of a board stored as XOR deltas
+//!
+//! A delta only holds the words that differ between two states, XORing it into one of the
+//! states gives the other one. So it goes both ways.
+
+use crate::BitboardInternal;
+
+/// The changed words between two boards, as (word index, XOR of the words)
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Delta {
+    words: Vec<(usize, u64)>,
+}
+
+impl Delta {
+    fn between<const N: usize, const R: usize>(from: &BitboardInternal<N, R>, to: &BitboardInternal<N, R>) -> Self {
+        let words = from
+            .words
+            .iter()
+            .zip(to.words.iter())
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(index, (a, b))| (index, a ^ b))
+            .collect();
+        Self { words }
+    }
+
+    fn apply<const N: usize, const R: usize>(&self, board: &mut BitboardInternal<N, R>) {
+        for &(index, xor) in self.words.iter() {
+            board.words[index] ^= xor;
+        }
+    }
+}
+
+/// Undo/redo history of a board, the revision 0 being the initial board
+#[derive(Clone)]
+pub struct BoardHistory<const N: usize, const R: usize> {
+    current: BitboardInternal<N, R>,
+    revision: usize,
+    // deltas[i] goes from the revision i to the revision i + 1
+    deltas: Vec<Delta>,
+}
+
+impl<const N: usize, const R: usize> BoardHistory<N, R> {
+    /// Creates the history starting at the given board
+    pub fn new(initial: BitboardInternal<N, R>) -> Self {
+        Self {
+            current: initial,
+            revision: 0,
+            deltas: Vec::new(),
+        }
+    }
+
+    /// Returns the board at the current revision
+    #[inline]
+    pub fn current(&self) -> &BitboardInternal<N, R> {
+        &self.current
+    }
+
+    /// Returns the current revision
+    #[inline]
+    pub fn revision(&self) -> usize {
+        self.revision
+    }
+
+    /// Returns the number of revisions, the undone ones included
+    #[inline]
+    pub fn revision_count(&self) -> usize {
+        self.deltas.len() + 1
+    }
+
+    /// Records the board as a new revision after the current one and returns it
+    /// The undone revisions are dropped
+    pub fn commit(&mut self, board: &BitboardInternal<N, R>) -> usize {
+        self.deltas.truncate(self.revision);
+        self.deltas.push(Delta::between(&self.current, board));
+        self.current = *board;
+        self.revision += 1;
+        self.revision
+    }
+
+    /// Goes back to the previous revision, returns false if there is none
+    pub fn undo(&mut self) -> bool {
+        if self.revision == 0 {
+            return false;
+        }
+        self.revision -= 1;
+        self.deltas[self.revision].apply(&mut self.current);
+        true
+    }
+
+    /// Goes to the next revision, returns false if there is none
+    pub fn redo(&mut self) -> bool {
+        if self.revision == self.deltas.len() {
+            return false;
+        }
+        self.deltas[self.revision].apply(&mut self.current);
+        self.revision += 1;
+        true
+    }
+
+    /// Goes to the given revision, the ones after it can still be redone
+    pub fn jump_to(&mut self, revision: usize) {
+        assert!(revision < self.revision_count(), "No such revision");
+        while self.revision > revision {
+            self.undo();
+        }
+        while self.revision < revision {
+            self.redo();
+        }
+    }
+
+    /// Returns the number of stored words of the deltas
+    pub fn delta_words(&self) -> usize {
+        self.deltas.iter().map(|delta| delta.words.len()).sum()
+    }
+}
+
+/// A sequence of boards stored as XOR deltas, with a full board every keyframe_interval
+/// states for random access
+#[derive(Clone)]
+pub struct BoardTimeline<const N: usize, const R: usize> {
+    keyframe_interval: usize,
+    // the states 0, keyframe_interval, 2 * keyframe_interval ...
+    keyframes: Vec<BitboardInternal<N, R>>,
+    // deltas[i] goes from the state i to the state i + 1
+    deltas: Vec<Delta>,
+    last: Option<BitboardInternal<N, R>>,
+}
+
+impl<const N: usize, const R: usize> BoardTimeline<N, R> {
+    /// Creates an empty timeline
+    pub fn new(keyframe_interval: usize) -> Self {
+        assert!(keyframe_interval > 0, "The keyframe interval can't be 0");
+        Self {
+            keyframe_interval,
+            keyframes: Vec::new(),
+            deltas: Vec::new(),
+            last: None,
+        }
+    }
+
+    /// Returns the number of states
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.keyframes.len().min(1) + self.deltas.len()
+    }
+
+    /// Returns wether there is no state
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.last.is_none()
+    }
+
+    /// Appends a state at the end of the timeline
+    pub fn push(&mut self, board: &BitboardInternal<N, R>) {
+        if let Some(last) = &self.last {
+            self.deltas.push(Delta::between(last, board));
+        }
+        if self.deltas.len().is_multiple_of(self.keyframe_interval) {
+            self.keyframes.push(*board);
+        }
+        self.last = Some(*board);
+    }
+
+    /// Returns the state at the given position, None if it is past the end
+    pub fn get(&self, index: usize) -> Option<BitboardInternal<N, R>> {
+        if index >= self.len() {
+            return None;
+        }
+        let keyframe = index / self.keyframe_interval;
+        let mut board = self.keyframes[keyframe];
+        for delta in self.deltas[keyframe * self.keyframe_interval..index].iter() {
+            delta.apply(&mut board);
+        }
+        Some(board)
+    }
+
+    /// Returns the last state
+    #[inline]
+    pub fn last(&self) -> Option<&BitboardInternal<N, R>> {
+        self.last.as_ref()
+    }
+
+    /// Returns an iterator over the states from the given position, replaying the deltas
+    pub fn iter_from(&self, index: usize) -> TimelineIter<'_, N, R> {
+        TimelineIter {
+            deltas: &self.deltas[index.min(self.deltas.len())..],
+            current: self.get(index),
+        }
+    }
+
+    /// Returns an iterator over all the states
+    #[inline]
+    pub fn iter(&self) -> TimelineIter<'_, N, R> {
+        self.iter_from(0)
+    }
+
+    /// Returns the number of stored words, keyframes and deltas
+    pub fn stored_words(&self) -> usize {
+        self.keyframes.len() * N + self.deltas.iter().map(|delta| delta.words.len()).sum::<usize>()
+    }
+}
+
+/// Iterator over the states of a timeline, see BoardTimeline::iter
+pub struct TimelineIter<'a, const N: usize, const R: usize> {
+    // the deltas following the current state
+    deltas: &'a [Delta],
+    current: Option<BitboardInternal<N, R>>,
+}
+
+impl<'a, const N: usize, const R: usize> Iterator for TimelineIter<'a, N, R> {
+    type Item = BitboardInternal<N, R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.current.take()?;
+        if let Some((delta, rest)) = self.deltas.split_first() {
+            let mut next = current;
+            delta.apply(&mut next);
+            self.current = Some(next);
+            self.deltas = rest;
+        }
+        Some(current)
+    }
+}
@@ -0,0 +1,365 @@
+//! Hybrid sparse/dense bitboards
+//!
+//! The indices are split in chunks of 2^16 bits, each non empty chunk being stored either as
+//! a sorted array of its set bits or as a dense bitboard, depending on its density (the same
+//! layout as roaring bitmaps). Empty chunks are not stored at all.
+
+use crate::{word_ones, Bitboard};
+use std::cmp::Ordering;
+use std::ops::{AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Sub, SubAssign};
+
+const CHUNK_BITS: usize = 1 << 16;
+/// A chunk with more set bits than this is stored as a dense bitboard
+const SPARSE_LIMIT: usize = 4096;
+
+type Chunk = Bitboard<CHUNK_BITS>;
+
+#[derive(Clone)]
+enum Container {
+    Sparse(Vec<u16>),
+    Dense { bits: Box<Chunk>, count: usize },
+}
+
+/// A set of bits of unbounded size switching between sparse and dense storage per chunk
+#[derive(Clone, Default)]
+pub struct HybridBitboard {
+    // sorted by chunk index
+    chunks: Vec<(usize, Container)>,
+}
+
+impl Container {
+    fn count(&self) -> usize {
+        match self {
+            Container::Sparse(values) => values.len(),
+            Container::Dense { count, .. } => *count,
+        }
+    }
+
+    fn contains(&self, value: u16) -> bool {
+        match self {
+            Container::Sparse(values) => values.binary_search(&value).is_ok(),
+            Container::Dense { bits, .. } => bits.is_set(value as usize),
+        }
+    }
+
+    fn insert(&mut self, value: u16) {
+        match self {
+            Container::Sparse(values) => {
+                if let Err(position) = values.binary_search(&value) {
+                    values.insert(position, value);
+                }
+            }
+            Container::Dense { bits, count } => {
+                if bits.is_unset(value as usize) {
+                    bits.set(value as usize);
+                    *count += 1;
+                }
+            }
+        }
+        self.normalize();
+    }
+
+    fn remove(&mut self, value: u16) {
+        match self {
+            Container::Sparse(values) => {
+                if let Ok(position) = values.binary_search(&value) {
+                    values.remove(position);
+                }
+            }
+            Container::Dense { bits, count } => {
+                if bits.is_set(value as usize) {
+                    bits.unset(value as usize);
+                    *count -= 1;
+                }
+            }
+        }
+        self.normalize();
+    }
+
+    fn values(&self) -> Box<dyn Iterator<Item = u16> + '_> {
+        match self {
+            Container::Sparse(values) => Box::new(values.iter().copied()),
+            Container::Dense { bits, .. } => Box::new(
+                bits.words
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(index, &word)| word_ones(word, index * 64))
+                    .map(|value| value as u16),
+            ),
+        }
+    }
+
+    fn to_dense(&self) -> Box<Chunk> {
+        match self {
+            Container::Sparse(values) => {
+                let mut bits = Chunk::new_boxed();
+                for &value in values.iter() {
+                    bits.set(value as usize);
+                }
+                bits
+            }
+            Container::Dense { bits, .. } => bits.clone(),
+        }
+    }
+
+    /// Combines two containers, see HybridBitboard::combine
+    fn combine<W, K>(&self, other: &Self, word_op: W, keep: K) -> Self
+    where
+        W: Fn(u64, u64) -> u64,
+        K: Fn(bool, bool) -> bool,
+    {
+        if let (Container::Sparse(a), Container::Sparse(b)) = (self, other) {
+            let mut result = Container::Sparse(merge_sorted(a, b, keep));
+            result.normalize();
+            result
+        } else {
+            self.combine_dense(other, word_op)
+        }
+    }
+
+    /// Word by word combination of two containers, used when one of them is dense
+    fn combine_dense<F: Fn(u64, u64) -> u64>(&self, other: &Self, op: F) -> Self {
+        let mut bits = self.to_dense();
+        let other_bits = other.to_dense();
+        let mut count = 0;
+        for (word, &other_word) in bits.words.iter_mut().zip(other_bits.words.iter()) {
+            *word = op(*word, other_word);
+            count += word.count_ones() as usize;
+        }
+        let mut result = Container::Dense { bits, count };
+        result.normalize();
+        result
+    }
+
+    /// Switches to the representation matching the density of the container
+    fn normalize(&mut self) {
+        match self {
+            Container::Sparse(values) if values.len() > SPARSE_LIMIT => {
+                let count = values.len();
+                *self = Container::Dense {
+                    bits: self.to_dense(),
+                    count,
+                };
+            }
+            Container::Dense { count, .. } if *count <= SPARSE_LIMIT => {
+                *self = Container::Sparse(self.values().collect());
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Merges two sorted arrays, keeping the values for which keep(in first, in second) is true
+fn merge_sorted<F: Fn(bool, bool) -> bool>(first: &[u16], second: &[u16], keep: F) -> Vec<u16> {
+    let mut result = Vec::with_capacity(first.len().max(second.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < first.len() || j < second.len() {
+        let (value, in_first, in_second) = match (first.get(i), second.get(j)) {
+            (Some(&a), Some(&b)) if a == b => (a, true, true),
+            (Some(&a), Some(&b)) if a < b => (a, true, false),
+            (Some(&a), None) => (a, true, false),
+            (_, Some(&b)) => (b, false, true),
+            (None, None) => unreachable!(),
+        };
+        i += in_first as usize;
+        j += in_second as usize;
+        if keep(in_first, in_second) {
+            result.push(value);
+        }
+    }
+    result
+}
+
+impl HybridBitboard {
+    /// Creates a new empty hybrid bitboard
+    pub fn new() -> Self {
+        Self { chunks: Vec::new() }
+    }
+
+    /// Sets the ith bit
+    pub fn set(&mut self, index: usize) {
+        let (key, value) = (index / CHUNK_BITS, (index % CHUNK_BITS) as u16);
+        match self.chunks.binary_search_by_key(&key, |&(key, _)| key) {
+            Ok(position) => self.chunks[position].1.insert(value),
+            Err(position) => self.chunks.insert(position, (key, Container::Sparse(vec![value]))),
+        }
+    }
+
+    /// Unsets the ith bit
+    pub fn unset(&mut self, index: usize) {
+        let (key, value) = (index / CHUNK_BITS, (index % CHUNK_BITS) as u16);
+        if let Ok(position) = self.chunks.binary_search_by_key(&key, |&(key, _)| key) {
+            let container = &mut self.chunks[position].1;
+            container.remove(value);
+            if container.count() == 0 {
+                self.chunks.remove(position);
+            }
+        }
+    }
+
+    /// Returns wether or not the given bit is set
+    pub fn is_set(&self, index: usize) -> bool {
+        let (key, value) = (index / CHUNK_BITS, (index % CHUNK_BITS) as u16);
+        match self.chunks.binary_search_by_key(&key, |&(key, _)| key) {
+            Ok(position) => self.chunks[position].1.contains(value),
+            Err(_) => false,
+        }
+    }
+
+    /// Returns wether or not the given bit is unset
+    #[inline]
+    pub fn is_unset(&self, index: usize) -> bool {
+        !self.is_set(index)
+    }
+
+    /// Returns wether the hybrid bitboard is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Returns the number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.chunks.iter().map(|(_, container)| container.count()).sum()
+    }
+
+    /// Returns the number of chunks stored as sorted arrays and as dense bitboards
+    pub fn container_counts(&self) -> (usize, usize) {
+        let sparse = self
+            .chunks
+            .iter()
+            .filter(|(_, container)| matches!(container, Container::Sparse(_)))
+            .count();
+        (sparse, self.chunks.len() - sparse)
+    }
+
+    /// Returns the number of bytes allocated on the heap, the unused capacity included
+    pub fn heap_size_bytes(&self) -> usize {
+        let containers: usize = self
+            .chunks
+            .iter()
+            .map(|(_, container)| match container {
+                Container::Sparse(values) => values.capacity() * std::mem::size_of::<u16>(),
+                Container::Dense { .. } => std::mem::size_of::<Chunk>(),
+            })
+            .sum();
+        self.chunks.capacity() * std::mem::size_of::<(usize, Container)>() + containers
+    }
+
+    /// Releases the unused capacity and switches every chunk to the storage matching its
+    /// density
+    pub fn shrink_to_fit(&mut self) {
+        for (_, container) in self.chunks.iter_mut() {
+            container.normalize();
+            if let Container::Sparse(values) = container {
+                values.shrink_to_fit();
+            }
+        }
+        self.chunks.shrink_to_fit();
+    }
+
+    /// Returns the proportion of set bits in the stored chunks, 0 if there is none
+    pub fn density(&self) -> f64 {
+        if self.chunks.is_empty() {
+            return 0.0;
+        }
+        self.count_ones() as f64 / (self.chunks.len() * CHUNK_BITS) as f64
+    }
+
+    /// Returns an iterator over the indices of the set bits, in increasing order
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.chunks
+            .iter()
+            .flat_map(|(key, container)| container.values().map(move |value| key * CHUNK_BITS + value as usize))
+    }
+
+    /// Keeps only the bits that are also set in other
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.combine(other, |a, b| a & b, |a, b| a && b);
+    }
+
+    /// Combines both sides chunk by chunk, keep tells if a bit is kept given its presence on
+    /// each side and word_op does the same for whole words
+    fn combine<W, K>(&mut self, other: &Self, word_op: W, keep: K)
+    where
+        W: Fn(u64, u64) -> u64,
+        K: Fn(bool, bool) -> bool,
+    {
+        let mut chunks = Vec::with_capacity(self.chunks.len().max(other.chunks.len()));
+        let mut own = std::mem::take(&mut self.chunks).into_iter().peekable();
+        let mut others = other.chunks.iter().peekable();
+        loop {
+            let order = match (own.peek(), others.peek()) {
+                (Some((a, _)), Some((b, _))) => a.cmp(b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            let chunk = match order {
+                Ordering::Less => own.next().filter(|_| keep(true, false)),
+                Ordering::Greater => others.next().filter(|_| keep(false, true)).cloned(),
+                Ordering::Equal => {
+                    let (key, first) = own.next().unwrap();
+                    let (_, second) = others.next().unwrap();
+                    Some((key, first.combine(second, &word_op, &keep)))
+                }
+            };
+            if let Some(chunk) = chunk.filter(|(_, container)| container.count() > 0) {
+                chunks.push(chunk);
+            }
+        }
+        self.chunks = chunks;
+    }
+}
+
+/// Implements a set operation assigning to the left side, by value and by reference, and the
+/// operator returning a new hybrid bitboard if one is given
+/// The words and keep closures are the ones of HybridBitboard::combine
+macro_rules! set_operator {
+    ($assign_trait:ident, $assign:ident, $word_op:expr, $keep:expr) => {
+        impl $assign_trait<&Self> for HybridBitboard {
+            fn $assign(&mut self, other: &Self) {
+                self.combine(other, $word_op, $keep);
+            }
+        }
+        impl $assign_trait for HybridBitboard {
+            #[inline]
+            fn $assign(&mut self, other: Self) {
+                self.$assign(&other);
+            }
+        }
+    };
+    ($op_trait:ident, $op:ident, $assign_trait:ident, $assign:ident, $word_op:expr, $keep:expr) => {
+        set_operator!($assign_trait, $assign, $word_op, $keep);
+
+        impl $op_trait for HybridBitboard {
+            type Output = Self;
+
+            #[inline]
+            fn $op(mut self, other: Self) -> Self {
+                self.$assign(&other);
+                self
+            }
+        }
+        impl $op_trait for &HybridBitboard {
+            type Output = HybridBitboard;
+
+            #[inline]
+            fn $op(self, other: Self) -> HybridBitboard {
+                let mut result = self.clone();
+                result.$assign(other);
+                result
+            }
+        }
+    };
+}
+
+// union
+set_operator!(AddAssign, add_assign, |a, b| a | b, |a, b| a || b);
+set_operator!(BitOr, bitor, BitOrAssign, bitor_assign, |a, b| a | b, |a, b| a || b);
+// intersection
+set_operator!(BitAnd, bitand, BitAndAssign, bitand_assign, |a, b| a & b, |a, b| a && b);
+// symmetric difference
+set_operator!(BitXor, bitxor, BitXorAssign, bitxor_assign, |a, b| a ^ b, |a, b| a != b);
+// set substraction
+set_operator!(Sub, sub, SubAssign, sub_assign, |a, b| a & !b, |a, b| a && !b);
@@ -0,0 +1,121 @@
+//! Typed indices
+//!
+//! The bits are stored by flat indices, counted line by line on 2D boards. The newtypes
+//! implementing BitIndex, like Coord, need a line size to be converted, so they are accepted
+//! wherever it is known: by the accessors of Bitboard2D, ComponentTracker and the grid helpers
+//! below, with the line size of the grid. Plain usize is a BitIndex as well.
+//!
+//! The accessors of BitboardInternal and DynBitboard take the BoardIndex of the board, whose
+//! line size is fixed by the board type: any board takes flat indices and Bitboard<64> takes
+//! chess squares, on lines of 8 cells.
+
+use crate::dynamic::BoardAllocator;
+use crate::{BitboardInternal, Direction, DynBitboard, Grid};
+
+/// An index of a bit, in a board made of lines of line_size cells
+pub trait BitIndex: Copy {
+    /// Returns the flat index of the bit
+    fn to_flat(self, line_size: usize) -> usize;
+    /// Returns the typed index of the given flat index
+    fn from_flat(index: usize, line_size: usize) -> Self;
+}
+
+impl BitIndex for usize {
+    #[inline]
+    fn to_flat(self, _line_size: usize) -> usize {
+        self
+    }
+    #[inline]
+    fn from_flat(index: usize, _line_size: usize) -> Self {
+        index
+    }
+}
+
+/// A BitIndex accepted by the accessors of the board B, converted with the line size B gives
+/// to the index type
+pub trait BoardIndex<B: ?Sized>: BitIndex {
+    /// The line size of the board for this index type
+    const LINE_SIZE: usize;
+}
+
+/// The bitboard seen as a single line
+impl<const N: usize, const R: usize> BoardIndex<BitboardInternal<N, R>> for usize {
+    const LINE_SIZE: usize = BitboardInternal::<N, R>::BITS;
+}
+
+/// Flat indices don't depend on the line size, which is only known at runtime
+impl<A: BoardAllocator> BoardIndex<DynBitboard<A>> for usize {
+    const LINE_SIZE: usize = 1;
+}
+
+/// The cell in the column x of the line y, Coord(x, y)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Coord(pub usize, pub usize);
+
+impl BitIndex for Coord {
+    #[inline]
+    fn to_flat(self, line_size: usize) -> usize {
+        assert!(self.0 < line_size, "Column out of the line");
+        self.1 * line_size + self.0
+    }
+    #[inline]
+    fn from_flat(index: usize, line_size: usize) -> Self {
+        Coord(index % line_size, index / line_size)
+    }
+}
+
+impl From<(usize, usize)> for Coord {
+    fn from((x, y): (usize, usize)) -> Self {
+        Coord(x, y)
+    }
+}
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Returns an iterator over the typed indices of the set bits, for lines of line_size cells
+    pub fn ones_as<I: BitIndex>(&self, line_size: usize) -> impl Iterator<Item = I> + '_ {
+        self.set_indices().map(move |index| I::from_flat(index, line_size))
+    }
+}
+
+impl<const N: usize, const R: usize> Grid<N, R> {
+    /// Returns the flat index of a typed index of the grid
+    #[inline]
+    pub fn flat<I: BitIndex>(&self, index: I) -> usize {
+        let flat = index.to_flat(self.line_size());
+        assert!(flat < self.line_count() * self.line_size(), "Index out of the grid");
+        flat
+    }
+
+    /// Returns the typed index of a flat index of the grid
+    #[inline]
+    pub fn typed<I: BitIndex>(&self, index: usize) -> I {
+        I::from_flat(index, self.line_size())
+    }
+
+    /// Sets the cell at the given typed index
+    pub fn set_at<I: BitIndex>(&self, board: &mut BitboardInternal<N, R>, index: I) {
+        board.set(self.flat(index));
+    }
+    /// Unsets the cell at the given typed index
+    pub fn unset_at<I: BitIndex>(&self, board: &mut BitboardInternal<N, R>, index: I) {
+        board.unset(self.flat(index));
+    }
+    /// Returns wether or not the cell at the given typed index is set
+    pub fn is_set_at<I: BitIndex>(&self, board: &BitboardInternal<N, R>, index: I) -> bool {
+        board.is_set(self.flat(index))
+    }
+
+    /// Returns an iterator over the typed indices of the set cells of the grid
+    pub fn ones_at<'a, I: BitIndex + 'a>(&self, board: &'a BitboardInternal<N, R>) -> impl Iterator<Item = I> + 'a {
+        let (line_size, cell_count) = (self.line_size(), self.line_count() * self.line_size());
+        board
+            .set_indices()
+            .take_while(move |&index| index < cell_count)
+            .map(move |index| I::from_flat(index, line_size))
+    }
+
+    /// Same as neighbor with typed indices
+    pub fn neighbor_at<I: BitIndex>(&self, index: I, direction: Direction) -> Option<I> {
+        self.neighbor(self.flat(index), direction).map(|neighbor| self.typed(neighbor))
+    }
+}
@@ -0,0 +1,157 @@
+//! Iterators over the indices of the set bits
+//!
+//! BitIterator walks the words with trailing_zeros from the front and leading_zeros from the
+//! back, so a sparse bitboard costs a step per set bit and per word rather than per bit. It
+//! holds the words by reference for iter and by value for into_iter.
+
+use crate::BitboardInternal;
+use std::iter::FusedIterator;
+
+/// An iterator over the indices of the set bits of words, in increasing order or from the high
+/// end with next_back
+#[derive(Clone, Debug)]
+pub struct BitIterator<W> {
+    words: W,
+    // mask of the valid bits of the last word
+    last_mask: u64,
+    // the words front and back, both included, and their bits not yielded yet
+    front: usize,
+    back: usize,
+    front_bits: u64,
+    back_bits: u64,
+    done: bool,
+}
+
+impl<W: AsRef<[u64]>> BitIterator<W> {
+    /// Iterates over the set bits of the words, last_mask giving the valid bits of the last
+    /// word
+    pub(crate) fn new(words: W, last_mask: u64) -> Self {
+        let len = words.as_ref().len();
+        let mut iter = Self {
+            words,
+            last_mask,
+            front: 0,
+            back: len.saturating_sub(1),
+            front_bits: 0,
+            back_bits: 0,
+            done: len == 0,
+        };
+        if !iter.done {
+            iter.front_bits = iter.word(0);
+            iter.back_bits = iter.word(iter.back);
+        }
+        iter
+    }
+
+    /// Returns the valid bits of the ith word
+    #[inline]
+    fn word(&self, index: usize) -> u64 {
+        let words = self.words.as_ref();
+        if index == words.len() - 1 {
+            words[index] & self.last_mask
+        } else {
+            words[index]
+        }
+    }
+
+    /// Returns the bits not yielded yet of the front word, both ends clear it when they meet
+    #[inline]
+    fn front_remaining(&self) -> u64 {
+        if self.front == self.back {
+            self.front_bits & self.back_bits
+        } else {
+            self.front_bits
+        }
+    }
+    #[inline]
+    fn back_remaining(&self) -> u64 {
+        if self.front == self.back {
+            self.front_bits & self.back_bits
+        } else {
+            self.back_bits
+        }
+    }
+}
+
+impl<W: AsRef<[u64]>> Iterator for BitIterator<W> {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        while !self.done {
+            let bits = self.front_remaining();
+            if bits != 0 {
+                let bit = bits.trailing_zeros() as usize;
+                self.front_bits &= !(1 << bit);
+                return Some(self.front * 64 + bit);
+            }
+            if self.front == self.back {
+                self.done = true;
+            } else {
+                self.front += 1;
+                self.front_bits = self.word(self.front);
+            }
+        }
+        None
+    }
+
+    fn count(self) -> usize {
+        if self.done {
+            return 0;
+        }
+        if self.front == self.back {
+            return self.front_remaining().count_ones() as usize;
+        }
+        let middle = (self.front + 1..self.back).map(|index| self.word(index).count_ones() as usize);
+        self.front_bits.count_ones() as usize + middle.sum::<usize>() + self.back_bits.count_ones() as usize
+    }
+}
+
+impl<W: AsRef<[u64]>> DoubleEndedIterator for BitIterator<W> {
+    #[inline]
+    fn next_back(&mut self) -> Option<usize> {
+        while !self.done {
+            let bits = self.back_remaining();
+            if bits != 0 {
+                let bit = 63 - bits.leading_zeros() as usize;
+                self.back_bits &= !(1 << bit);
+                return Some(self.back * 64 + bit);
+            }
+            if self.front == self.back {
+                self.done = true;
+            } else {
+                self.back -= 1;
+                self.back_bits = self.word(self.back);
+            }
+        }
+        None
+    }
+}
+
+impl<W: AsRef<[u64]>> FusedIterator for BitIterator<W> {}
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Returns an iterator over the indices of the set bits, junk bits excluded
+    #[inline]
+    pub fn iter(&self) -> BitIterator<&[u64]> {
+        BitIterator::new(&self.words, Self::LAST_WORD_MASK)
+    }
+}
+
+/// The indices of the set bits, junk bits excluded
+impl<const N: usize, const R: usize> IntoIterator for BitboardInternal<N, R> {
+    type Item = usize;
+    type IntoIter = BitIterator<[u64; N]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        BitIterator::new(self.words, Self::LAST_WORD_MASK)
+    }
+}
+impl<'a, const N: usize, const R: usize> IntoIterator for &'a BitboardInternal<N, R> {
+    type Item = usize;
+    type IntoIter = BitIterator<&'a [u64]>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -1,6 +1,76 @@
 #![feature(const_generics)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+#![cfg_attr(feature = "simd", feature(portable_simd))]
 
-use std::ops::{AddAssign, SubAssign};
+use std::convert::TryInto;
+use std::fmt;
+use std::ops::{
+    AddAssign, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Index, Not, Range, Shl, ShlAssign,
+    Shr, ShrAssign, SubAssign,
+};
+
+pub use automaton::Rule;
+pub use board2d::Bitboard2D;
+pub use dynamic::DynBitboard;
+pub use grid::{Connectivity, Direction, Grid, Ties, Topology};
+pub use index::{BitIndex, BoardIndex, Coord};
+pub use iter::BitIterator;
+pub use ops::BitboardOps;
+pub use order::BitOrder;
+pub use view::{BitView, BitViewMut};
+
+#[cfg(feature = "image")]
+pub mod animation;
+pub mod arith;
+pub mod automaton;
+pub mod board2d;
+mod bulk;
+#[cfg(feature = "chess")]
+pub mod chess;
+pub mod checksum;
+pub mod components;
+pub mod compressed;
+pub mod dna;
+pub mod dynamic;
+pub mod edit_distance;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod gf2;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+pub mod graph;
+pub mod grid;
+pub mod hex;
+pub mod history;
+pub mod hybrid;
+pub mod index;
+pub mod iter;
+pub mod ops;
+pub mod order;
+pub mod planes;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod render;
+#[cfg(feature = "serde")]
+mod serialization;
+pub mod set;
+pub mod shift_or;
+pub mod stream;
+pub mod summary;
+pub mod sudoku;
+pub mod symmetry;
+pub mod view;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+/// Builds a Bitboard of the given size with the given bits set, usable for constants as in
+/// const FILE_A: Bitboard<64> = bitboard!(64; 0, 8, 16, 24, 32, 40, 48, 56);
+#[macro_export]
+macro_rules! bitboard {
+    ($bits:expr; $($index:expr),* $(,)?) => {
+        $crate::Bitboard::<$bits>::from_indices(&[$($index),*])
+    };
+}
 
 /// A Bitboard of N bits
 /// N has to be different than 0
@@ -8,137 +78,710 @@ pub type Bitboard<const N: usize> = BitboardInternal<{(N-1) / 64 + 1}, {(N-1) %
 
 /// Internal structure for Bitboard, N is the number of 64 bits words and R is the index of the
 /// last valid bit in the last word
-#[derive(Clone)]
+#[derive(Clone, Copy)]
+#[repr(transparent)]
 pub struct BitboardInternal<const N: usize, const R: usize> {
     words: [u64; N],
 }
 
 impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Number of valid bits in the Bitboard
+    pub const BITS: usize = (N - 1) * 64 + R + 1;
+    /// Mask of the valid bits of the last word
+    const LAST_WORD_MASK: u64 = !0 >> (63 - R);
+
     /// Creates a new empty Bitboard
     #[inline]
-    pub fn new() -> Self {
-        Self {
-            words: unsafe { std::mem::zeroed() },
+    pub const fn new() -> Self {
+        Self { words: [0; N] }
+    }
+
+    /// Creates a Bitboard with the given bits set, usable for constants
+    pub const fn from_indices(indices: &[usize]) -> Self {
+        let mut board = Self::new();
+        let mut i = 0;
+        while i < indices.len() {
+            board = board.with_bit(indices[i]);
+            i += 1;
         }
+        board
     }
 
-    /// Sets the ith bit of the Bitboard
+    /// Returns the Bitboard with the ith bit set as well, usable for constants
     #[inline]
-    pub fn set(&mut self, index: usize) {
+    pub const fn with_bit(mut self, index: usize) -> Self {
         self.set_word(index, 1);
+        self
     }
-    /// Unsets the ith bit of the Bitboard
+    /// Returns the Bitboard with the ith bit unset, usable for constants
     #[inline]
-    pub fn unset(&mut self, index: usize) {
+    pub const fn without_bit(mut self, index: usize) -> Self {
         let (word, mask) = self.word_mask_mut(index);
         *word &= !mask;
+        self
     }
-    /// Returns wether or not the given bit is set
+    /// Returns wether or not the ith bit is set, usable for constants
     #[inline]
-    pub fn is_set(&self, index: usize) -> bool {
+    pub const fn has_bit(&self, index: usize) -> bool {
         let (word, mask) = self.word_mask(index);
-        word & mask != 0
+        *word & mask != 0
+    }
+
+    /// Creates a Bitboard from its raw words, the bit i being the bit i % 64 of the word i / 64
+    /// The junk bits are kept but they are ignored by the operations
+    #[inline]
+    pub const fn from_words(words: [u64; N]) -> Self {
+        Self { words }
+    }
+    /// Returns the raw words of the Bitboard, junk bits included
+    #[inline]
+    pub const fn as_words(&self) -> &[u64; N] {
+        &self.words
+    }
+    /// Returns the raw words of the Bitboard, junk bits included
+    #[inline]
+    pub const fn into_words(self) -> [u64; N] {
+        self.words
+    }
+
+    /// Creates a new empty Bitboard directly on the heap, huge bitboards don't fit on the stack
+    pub fn new_boxed() -> Box<Self> {
+        let words: Box<[u64; N]> = vec![0; N].into_boxed_slice().try_into().unwrap();
+        // This is sound because BitboardInternal is a transparent wrapper around its words
+        unsafe { Box::from_raw(Box::into_raw(words) as *mut Self) }
+    }
+
+    /// Creates a new empty Bitboard directly in the given allocator
+    #[cfg(feature = "allocator_api")]
+    pub fn new_boxed_in<A: std::alloc::Allocator>(allocator: A) -> Box<Self, A> {
+        // all zero words are an empty bitboard
+        unsafe { Box::new_zeroed_in(allocator).assume_init() }
+    }
+
+    /// Sets the given bit of the Bitboard, a flat index or a typed one, see BoardIndex
+    #[inline]
+    pub fn set<I: BoardIndex<Self>>(&mut self, index: I) {
+        self.set_word(index.to_flat(I::LINE_SIZE), 1);
+    }
+    /// Unsets the given bit of the Bitboard, a flat index or a typed one, see BoardIndex
+    #[inline]
+    pub fn unset<I: BoardIndex<Self>>(&mut self, index: I) {
+        let (word, mask) = self.word_mask_mut(index.to_flat(I::LINE_SIZE));
+        *word &= !mask;
+    }
+    /// Sets or unsets the given bit of the Bitboard depending on value
+    #[inline]
+    pub fn assign<I: BoardIndex<Self>>(&mut self, index: I, value: bool) {
+        if value {
+            self.set(index);
+        } else {
+            self.unset(index);
+        }
+    }
+    /// Returns wether or not the given bit is set
+    #[inline]
+    pub fn is_set<I: BoardIndex<Self>>(&self, index: I) -> bool {
+        self.has_bit(index.to_flat(I::LINE_SIZE))
     }
     /// Returns wether or not the given bit is unset
     #[inline]
-    pub fn is_unset(&self, index: usize) -> bool {
+    pub fn is_unset<I: BoardIndex<Self>>(&self, index: I) -> bool {
         !self.is_set(index)
     }
     /// Returns wether the given Bitboard is empty
     pub fn is_empty(&self) -> bool {
-        self.words.iter().all(|&w| w == 0)
+        bulk::is_zero(&self.words[..N - 1]) && self.words[N - 1] & Self::LAST_WORD_MASK == 0
+    }
+    /// Returns the number of set bits, junk bits excluded
+    pub fn count_ones(&self) -> usize {
+        let last = (self.words[N - 1] & Self::LAST_WORD_MASK).count_ones() as usize;
+        bulk::count_ones(&self.words[..N - 1]) + last
+    }
+    /// Returns the index of the first set bit, None if the bitboard is empty
+    #[inline]
+    pub fn first_set(&self) -> Option<usize> {
+        self.iter().next()
+    }
+    /// Returns the index of the last set bit, None if the bitboard is empty
+    #[inline]
+    pub fn last_set(&self) -> Option<usize> {
+        self.iter().next_back()
+    }
+    /// Returns the index of the nth set bit counting from 0, None if there are not that many
+    /// The words before the one holding it are skipped with their popcount
+    pub fn select(&self, n: usize) -> Option<usize> {
+        select_in_words(self.iter_words(), n)
+    }
+
+    /// Returns an iterator over the words, the junk bits unset
+    #[inline]
+    fn iter_words(&self) -> impl Iterator<Item = u64> + '_ {
+        let last = self.words[N - 1] & Self::LAST_WORD_MASK;
+        self.words[..N - 1].iter().copied().chain(std::iter::once(last))
     }
+
     /// Flip the whole bitboard (equivalent to a not to itself)
     pub fn flip(&mut self) {
         for word in self.words.iter_mut() {
             *word = !*word;
         }
+        self.words[N - 1] &= Self::LAST_WORD_MASK;
+    }
+
+    /// Returns an iterator over the indices of the set bits, junk bits excluded
+    #[inline]
+    fn set_indices(&self) -> BitIterator<&[u64]> {
+        self.iter()
     }
 
     /// Returns a reference to the word pointed by the given index and a mask with the
     /// corresponding bit set
     #[inline]
-    fn word_mask(&self, index: usize) -> (&u64, u64) {
+    const fn word_mask(&self, index: usize) -> (&u64, u64) {
         (&self.words[index / 64], 1 << (index % 64))
     }
     /// Same as word_mask but with a mutable reference
     #[inline]
-    fn word_mask_mut(&mut self, index: usize) -> (&mut u64, u64) {
+    const fn word_mask_mut(&mut self, index: usize) -> (&mut u64, u64) {
         (&mut self.words[index / 64], 1 << (index % 64))
     }
     /// Sets a whole shifted word to the given index
     /// It is assumed that the shifted word fits in a single word of the bitboard
     /// the overflow is deleted
-    fn set_word(&mut self, index: usize, word: u64) {
+    const fn set_word(&mut self, index: usize, word: u64) {
         self.words[index / 64] |= word << (index % 64);
     }
 
-    /// Sets a whole line of bits
-    pub fn set_whole_line(&mut self, line_no: usize, line_size: usize) {
-        // We are using a single bitmask for the whole line
-        // TODO generic algorithm for line_size > 64
-        // The conditional becomes a loop
-        assert!(line_size < 64);
+    /// Shifts the whole bitboard by one bit towards the most significant bits
+    /// The carry is propagated between words and the overflow of the last word is deleted
+    #[inline]
+    fn shift_left_one(&mut self) {
+        let mut carry = 0;
+        for word in self.words.iter_mut() {
+            let next_carry = *word >> 63;
+            *word = (*word << 1) | carry;
+            carry = next_carry;
+        }
+    }
+    /// Shifts the whole bitboard by the given amount towards the most significant bits
+    /// The overflow of the last word is deleted
+    #[inline]
+    fn shift_left_by(&mut self, amount: usize) {
+        shift_words_left(&mut self.words, amount);
+    }
+    /// Shifts the whole bitboard by the given amount towards the least significant bits
+    #[inline]
+    fn shift_right_by(&mut self, amount: usize) {
+        shift_words_right(&mut self.words, amount);
+    }
 
-        let start_index = line_no * line_size;
-        let start_pos = start_index % 64;
+    /// Combines every board word by word with the same mask, op getting a word of the board
+    /// and the word of the mask, as |a, b| a & b to intersect them all with the mask
+    /// The words are processed by blocks so the mask block stays in cache over all the boards
+    pub fn apply_mask_all<F: Fn(u64, u64) -> u64>(boards: &mut [Self], mask: &Self, op: F) {
+        const BLOCK_WORDS: usize = 64;
+        for start in (0..N).step_by(BLOCK_WORDS) {
+            let end = N.min(start + BLOCK_WORDS);
+            let mask_block = &mask.words[start..end];
+            for board in boards.iter_mut() {
+                for (word, &mask_word) in board.words[start..end].iter_mut().zip(mask_block.iter()) {
+                    *word = op(*word, mask_word);
+                }
+            }
+        }
+    }
 
-        // A word composed of line_size ones as LSBs
-        let ones = (1 << line_size as u64) - 1;
+    /// Returns the width bits starting at the given index as the LSBs of a word
+    /// The field can span two words of the bitboard
+    #[inline]
+    pub fn get_field(&self, index: usize, width: usize) -> u64 {
+        read_field(&self.words, index, width)
+    }
+    /// Overwrites the width bits starting at the given index with the LSBs of value
+    /// The field can span two words of the bitboard
+    #[inline]
+    pub fn set_field(&mut self, index: usize, width: usize, value: u64) {
+        write_field(&mut self.words, index, width, value);
+    }
 
-        // if the line fits in a single word
-        if start_pos + line_size < 64 {
-            self.set_word(start_index, ones);
-        // if it fits in two words
-        } else {
-            // first word (the overflow is deleted)
-            self.set_word(start_index, ones);
-            // second word
-            let second_ones = ones >> (64 - start_pos);
-            let transition_index = start_index + 64 - start_pos;
-            self.set_word(transition_index, second_ones);
+    /// Returns a read only view of the given range of bits
+    #[inline]
+    pub fn view(&self, range: Range<usize>) -> BitView<'_> {
+        assert!(range.start <= range.end && range.end <= Self::BITS);
+        BitView::new(&self.words, range.start, range.end - range.start)
+    }
+
+    /// Returns a mutable view of the given range of bits, it can be split with
+    /// BitViewMut::split_at_mut
+    #[inline]
+    pub fn view_mut(&mut self, range: Range<usize>) -> BitViewMut<'_> {
+        assert!(range.start <= range.end && range.end <= Self::BITS);
+        BitViewMut::new(&mut self.words, range.start, range.end - range.start)
+    }
+
+    /// Returns a new bitboard where the bit i of this one is moved to the position perm[i]
+    /// perm has to be a permutation of the valid indices of the bitboard
+    pub fn permute(&self, perm: &[usize]) -> Self {
+        assert_eq!(perm.len(), Self::BITS);
+
+        let mut result = Self::new();
+        for (word_index, &word) in self.words.iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                let index = word_index * 64 + word.trailing_zeros() as usize;
+                // junk bits are not moved
+                if index >= Self::BITS {
+                    break;
+                }
+                result.set(perm[index]);
+                word &= word - 1;
+            }
+        }
+        result
+    }
+    /// Swaps the bits i and i + delta for every bit i set in the mask
+    /// Both bits of a swapped pair have to be valid and the mask can't contain both of them
+    pub fn delta_swap(&mut self, mask: &Self, delta: usize) {
+        let mut swapped = *self;
+        swapped.shift_right_by(delta);
+        let pairs = self.words.iter_mut().zip(swapped.words.iter_mut());
+        for ((word, swapped_word), &mask_word) in pairs.zip(mask.words.iter()) {
+            *swapped_word = (*swapped_word ^ *word) & mask_word;
+            *word ^= *swapped_word;
+        }
+        swapped.shift_left_by(delta);
+        for (word, &swapped_word) in self.words.iter_mut().zip(swapped.words.iter()) {
+            *word ^= swapped_word;
+        }
+    }
+    /// Applies a sequence of delta swaps given as (mask, delta) pairs, this is a much faster
+    /// alternative to permute for the permutations that can be decomposed this way
+    pub fn permute_delta_swaps(&mut self, steps: &[(Self, usize)]) {
+        for (mask, delta) in steps.iter() {
+            self.delta_swap(mask, *delta);
+        }
+    }
+
+    /// Returns the bitboard twice as long with the bits of self at even indices and the ones
+    /// of other at odd indices (bit i of self goes to 2i and bit i of other to 2i + 1)
+    pub fn interleave<const M: usize, const S: usize>(&self, other: &Self) -> BitboardInternal<M, S> {
+        assert_eq!(BitboardInternal::<M, S>::BITS, 2 * Self::BITS);
+
+        let mut result = BitboardInternal::new();
+        for (index, (&word, &other_word)) in self.words.iter().zip(other.words.iter()).enumerate() {
+            for half in 0..2 {
+                // the last half word of self may not be needed
+                if let Some(result_word) = result.words.get_mut(2 * index + half) {
+                    let (low, other_low) = (word >> (32 * half), other_word >> (32 * half));
+                    *result_word = spread_to_even_bits(low) | spread_to_even_bits(other_low) << 1;
+                }
+            }
+        }
+        result
+    }
+    /// Inverse of interleave, returns the bitboards made of the even and the odd bits
+    pub fn deinterleave<const M: usize, const S: usize>(&self) -> (BitboardInternal<M, S>, BitboardInternal<M, S>) {
+        assert_eq!(2 * BitboardInternal::<M, S>::BITS, Self::BITS);
+
+        let mut even = BitboardInternal::new();
+        let mut odd = BitboardInternal::new();
+        for (index, (even_word, odd_word)) in even.words.iter_mut().zip(odd.words.iter_mut()).enumerate() {
+            for half in 0..2 {
+                let word = self.words.get(2 * index + half).copied().unwrap_or(0);
+                *even_word |= compress_even_bits(word) << (32 * half);
+                *odd_word |= compress_even_bits(word >> 1) << (32 * half);
+            }
         }
+        (even, odd)
+    }
+
+    /// Returns the bitboard with the order of its bits reversed, the bit i goes to BITS - 1 - i
+    pub fn reverse_bits(&self) -> Self {
+        let mut result = Self::new();
+        for (word, &source) in result.words.iter_mut().zip(self.words.iter().rev()) {
+            *word = source.reverse_bits();
+        }
+        // the junk bits are now the LSBs, they are shifted out
+        result.shift_right_by(63 - R);
+        result
+    }
+    /// Returns the bitboard with the order of the bytes reversed inside every word
+    /// The last word is swapped as a whole so its junk bits are moved as well
+    pub fn swap_bytes_per_word(&self) -> Self {
+        let mut result = *self;
+        for word in result.words.iter_mut() {
+            *word = word.swap_bytes();
+        }
+        result
+    }
+
+    /// Sets a whole line of bits
+    pub fn set_whole_line(&mut self, line_no: usize, line_size: usize) {
+        let start = line_no * line_size;
+        self.set_range(start..start + line_size);
+    }
+
+    /// Sets all the bits of the range
+    #[inline]
+    pub fn set_range(&mut self, range: Range<usize>) {
+        assert!(range.start <= range.end && range.end <= Self::BITS, "Range out of the bitboard");
+        apply_range(&mut self.words, range, |word, mask| word | mask);
+    }
+    /// Unsets all the bits of the range
+    #[inline]
+    pub fn unset_range(&mut self, range: Range<usize>) {
+        assert!(range.start <= range.end && range.end <= Self::BITS, "Range out of the bitboard");
+        apply_range(&mut self.words, range, |word, mask| word & !mask);
+    }
+    /// Flips all the bits of the range
+    #[inline]
+    pub fn toggle_range(&mut self, range: Range<usize>) {
+        assert!(range.start <= range.end && range.end <= Self::BITS, "Range out of the bitboard");
+        apply_range(&mut self.words, range, |word, mask| word ^ mask);
     }
 
     /// Prints the whole bitboard lines by lines in a human readable way
     /// This is typically used for debugging so the "junk" is also printed
     pub fn print_by_line(&self, line_size: usize) {
+        let mut text = String::new();
+        self.write_by_line(&mut text, line_size, N * 64).unwrap();
+        println!("{}", text);
+    }
+
+    /// Writes the first bits of the bitboard lines by lines, as 0s and 1s
+    fn write_by_line<W: fmt::Write>(&self, out: &mut W, line_size: usize, bits: usize) -> fmt::Result {
         // TODO optimized loop taking care of transition between whole 64 bits words
-        for index in 0..N*64 {
+        for index in 0..bits {
             if index % line_size == 0 && index > 0 {
-                println!("");
+                writeln!(out)?;
             }
-            print!("{}", self.is_set(index) as u8);
+            write!(out, "{}", self.is_set(index) as u8)?;
+        }
+        Ok(())
+    }
+}
+
+/// The valid bits by lines of 64 bits, one line per word
+impl<const N: usize, const R: usize> fmt::Display for BitboardInternal<N, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_by_line(f, 64, Self::BITS)
+    }
+}
+/// The size of the bitboard followed by its valid bits as in Display
+impl<const N: usize, const R: usize> fmt::Debug for BitboardInternal<N, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Bitboard<{}>", Self::BITS)?;
+        self.write_by_line(f, 64, Self::BITS)
+    }
+}
+
+/// Returns the width bits of words starting at the given index as the LSBs of a word
+fn read_field(words: &[u64], index: usize, width: usize) -> u64 {
+    assert!(width <= 64);
+
+    let (word_index, shift) = (index / 64, index % 64);
+    let mut value = words[word_index] >> shift;
+    // shift is not 0 here, the field fits in a single word otherwise
+    if shift + width > 64 {
+        value |= words[word_index + 1] << (64 - shift);
+    }
+    value & field_mask(width)
+}
+
+/// Overwrites the width bits of words starting at the given index with the LSBs of value
+fn write_field(words: &mut [u64], index: usize, width: usize, value: u64) {
+    assert!(width <= 64);
+
+    let (word_index, shift) = (index / 64, index % 64);
+    let mask = field_mask(width);
+    let value = value & mask;
+
+    let word = &mut words[word_index];
+    *word = (*word & !(mask << shift)) | (value << shift);
+    if shift + width > 64 {
+        let written = 64 - shift;
+        let word = &mut words[word_index + 1];
+        *word = (*word & !(mask >> written)) | (value >> written);
+    }
+}
+
+/// A word composed of width ones as LSBs
+#[inline]
+fn field_mask(width: usize) -> u64 {
+    if width == 64 {
+        !0
+    } else {
+        (1 << width) - 1
+    }
+}
+
+/// Applies op to the words covered by the range, op getting a word and the mask of the bits
+/// of the range in it, the whole words in the middle get a full mask
+fn apply_range<F: Fn(u64, u64) -> u64>(words: &mut [u64], range: Range<usize>, op: F) {
+    if range.start == range.end {
+        return;
+    }
+    let (first, last) = (range.start / 64, (range.end - 1) / 64);
+    let first_mask = !0 << (range.start % 64);
+    let last_mask = !0 >> (63 - (range.end - 1) % 64);
+    if first == last {
+        words[first] = op(words[first], first_mask & last_mask);
+        return;
+    }
+    words[first] = op(words[first], first_mask);
+    for word in words[first + 1..last].iter_mut() {
+        *word = op(*word, !0);
+    }
+    words[last] = op(words[last], last_mask);
+}
+
+/// Shifts the words by the given amount towards the most significant bits
+/// The carry is propagated between words and the overflow of the last word is deleted
+fn shift_words_left(words: &mut [u64], amount: usize) {
+    let (word_shift, bit_shift) = (amount / 64, amount % 64);
+    for index in (0..words.len()).rev() {
+        let mut word = 0;
+        if index >= word_shift {
+            let source = index - word_shift;
+            word = words[source] << bit_shift;
+            if bit_shift > 0 && source > 0 {
+                word |= words[source - 1] >> (64 - bit_shift);
+            }
+        }
+        words[index] = word;
+    }
+}
+
+/// Shifts the words by the given amount towards the least significant bits
+fn shift_words_right(words: &mut [u64], amount: usize) {
+    let (word_shift, bit_shift) = (amount / 64, amount % 64);
+    for index in 0..words.len() {
+        let mut word = 0;
+        let source = index + word_shift;
+        if source < words.len() {
+            word = words[source] >> bit_shift;
+            if bit_shift > 0 && source + 1 < words.len() {
+                word |= words[source + 1] << (64 - bit_shift);
+            }
+        }
+        words[index] = word;
+    }
+}
+
+/// Returns the index of the nth set bit of the words
+fn select_in_words<I: Iterator<Item = u64>>(words: I, mut n: usize) -> Option<usize> {
+    for (index, mut word) in words.enumerate() {
+        let count = word.count_ones() as usize;
+        if n < count {
+            for _ in 0..n {
+                word &= word - 1;
+            }
+            return Some(index * 64 + word.trailing_zeros() as usize);
+        }
+        n -= count;
+    }
+    None
+}
+
+/// Returns an iterator over the indices of the set bits of a word, offset by base
+#[inline]
+fn word_ones(mut word: u64, base: usize) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if word == 0 {
+            return None;
+        }
+        let bit = word.trailing_zeros() as usize;
+        word &= word - 1;
+        Some(base + bit)
+    })
+}
+
+/// Spreads the 32 LSBs of a word to its even bits
+#[inline]
+fn spread_to_even_bits(word: u64) -> u64 {
+    let mut word = word & 0x0000_0000_ffff_ffff;
+    word = (word | (word << 16)) & 0x0000_ffff_0000_ffff;
+    word = (word | (word << 8)) & 0x00ff_00ff_00ff_00ff;
+    word = (word | (word << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    word = (word | (word << 2)) & 0x3333_3333_3333_3333;
+    (word | (word << 1)) & 0x5555_5555_5555_5555
+}
+
+/// Packs the 32 even bits of a word into its 32 LSBs, inverse of spread_to_even_bits
+#[inline]
+fn compress_even_bits(word: u64) -> u64 {
+    let mut word = word & 0x5555_5555_5555_5555;
+    word = (word | (word >> 1)) & 0x3333_3333_3333_3333;
+    word = (word | (word >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    word = (word | (word >> 4)) & 0x00ff_00ff_00ff_00ff;
+    word = (word | (word >> 8)) & 0x0000_ffff_0000_ffff;
+    (word | (word >> 16)) & 0x0000_0000_ffff_ffff
+}
+
+/// Reading a bit with board[index], this is the same as is_set
+impl<I: BoardIndex<BitboardInternal<N, R>>, const N: usize, const R: usize> Index<I> for BitboardInternal<N, R> {
+    type Output = bool;
+
+    #[inline]
+    fn index(&self, index: I) -> &bool {
+        // both constants are promoted to static references
+        if self.is_set(index) {
+            &true
+        } else {
+            &false
         }
-        println!("");
     }
 }
 
 /// Union between two same size sets of bits
+impl<const N: usize, const R: usize> AddAssign<&Self> for BitboardInternal<N, R> {
+    fn add_assign(&mut self, other: &Self) {
+        bulk::or_assign(&mut self.words, &other.words);
+    }
+}
 impl<const N: usize, const R: usize> AddAssign for BitboardInternal<N, R> {
+    #[inline]
     fn add_assign(&mut self, other: Self) {
-        for (word, other_word) in self.words.iter_mut().zip(other.words.iter()) {
-            *word |= other_word
-        }
+        *self += &other;
     }
 }
 /// Set substraction between two same size sets of bits
+impl<const N: usize, const R: usize> SubAssign<&Self> for BitboardInternal<N, R> {
+    fn sub_assign(&mut self, other: &Self) {
+        bulk::and_not_assign(&mut self.words, &other.words);
+    }
+}
 impl<const N: usize, const R: usize> SubAssign for BitboardInternal<N, R> {
+    #[inline]
     fn sub_assign(&mut self, other: Self) {
-        for (word, other_word) in self.words.iter_mut().zip(other.words.iter()) {
-            *word &= !other_word
+        *self -= &other;
+    }
+}
+
+/// Implements a word by word operator and its assign variant, by value and by reference
+/// The junk bits of the result are unset
+macro_rules! bitwise_operator {
+    ($op_trait:ident, $op:ident, $assign_trait:ident, $assign:ident, $kernel:path) => {
+        impl<const N: usize, const R: usize> $assign_trait<&Self> for BitboardInternal<N, R> {
+            #[inline]
+            fn $assign(&mut self, other: &Self) {
+                $kernel(&mut self.words, &other.words);
+                self.words[N - 1] &= Self::LAST_WORD_MASK;
+            }
+        }
+        impl<const N: usize, const R: usize> $assign_trait for BitboardInternal<N, R> {
+            #[inline]
+            fn $assign(&mut self, other: Self) {
+                self.$assign(&other);
+            }
+        }
+        impl<const N: usize, const R: usize> $op_trait for BitboardInternal<N, R> {
+            type Output = Self;
+
+            #[inline]
+            fn $op(mut self, other: Self) -> Self {
+                self.$assign(&other);
+                self
+            }
+        }
+        impl<const N: usize, const R: usize> $op_trait for &BitboardInternal<N, R> {
+            type Output = BitboardInternal<N, R>;
+
+            #[inline]
+            fn $op(self, other: Self) -> BitboardInternal<N, R> {
+                let mut result = *self;
+                result.$assign(other);
+                result
+            }
         }
+    };
+}
+
+bitwise_operator!(BitAnd, bitand, BitAndAssign, bitand_assign, bulk::and_assign);
+bitwise_operator!(BitOr, bitor, BitOrAssign, bitor_assign, bulk::or_assign);
+bitwise_operator!(BitXor, bitxor, BitXorAssign, bitxor_assign, bulk::xor_assign);
+
+/// The complement of the set of bits, the junk bits stay unset
+impl<const N: usize, const R: usize> Not for BitboardInternal<N, R> {
+    type Output = Self;
+
+    #[inline]
+    fn not(mut self) -> Self {
+        self.flip();
+        self
+    }
+}
+impl<const N: usize, const R: usize> Not for &BitboardInternal<N, R> {
+    type Output = BitboardInternal<N, R>;
+
+    #[inline]
+    fn not(self) -> BitboardInternal<N, R> {
+        !*self
     }
 }
 
-/*
-/// An iterator over the bits of a Bitboard
-impl<const N: usize, const R: usize>IntoIterator for BitboardInternal<N, R> {
-    type Item = bool;
-    type IntoIter = TODO;
+/// Shift towards the most significant bits, the bits going past BITS are lost
+impl<const N: usize, const R: usize> ShlAssign<usize> for BitboardInternal<N, R> {
+    #[inline]
+    fn shl_assign(&mut self, amount: usize) {
+        self.shift_left_by(amount);
+        self.words[N - 1] &= Self::LAST_WORD_MASK;
+    }
+}
+/// Shift towards the least significant bits, the junk bits are not shifted in
+impl<const N: usize, const R: usize> ShrAssign<usize> for BitboardInternal<N, R> {
+    #[inline]
+    fn shr_assign(&mut self, amount: usize) {
+        self.words[N - 1] &= Self::LAST_WORD_MASK;
+        self.shift_right_by(amount);
+    }
+}
+impl<const N: usize, const R: usize> Shl<usize> for BitboardInternal<N, R> {
+    type Output = Self;
+
+    #[inline]
+    fn shl(mut self, amount: usize) -> Self {
+        self <<= amount;
+        self
+    }
+}
+impl<const N: usize, const R: usize> Shr<usize> for BitboardInternal<N, R> {
+    type Output = Self;
+
+    #[inline]
+    fn shr(mut self, amount: usize) -> Self {
+        self >>= amount;
+        self
+    }
+}
+impl<const N: usize, const R: usize> Shl<usize> for &BitboardInternal<N, R> {
+    type Output = BitboardInternal<N, R>;
+
+    #[inline]
+    fn shl(self, amount: usize) -> BitboardInternal<N, R> {
+        *self << amount
+    }
+}
+impl<const N: usize, const R: usize> Shr<usize> for &BitboardInternal<N, R> {
+    type Output = BitboardInternal<N, R>;
+
+    #[inline]
+    fn shr(self, amount: usize) -> BitboardInternal<N, R> {
+        *self >> amount
+    }
+}
+
+/// Equality of the valid bits, the junk bits are ignored
+impl<const N: usize, const R: usize> PartialEq for BitboardInternal<N, R> {
+    fn eq(&self, other: &Self) -> bool {
+        bulk::equal(&self.words[..N - 1], &other.words[..N - 1])
+            && (self.words[N - 1] ^ other.words[N - 1]) & Self::LAST_WORD_MASK == 0
+    }
+}
+impl<const N: usize, const R: usize> Eq for BitboardInternal<N, R> {}
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.words.iter().flatten()
+impl<const N: usize, const R: usize> Default for BitboardInternal<N, R> {
+    fn default() -> Self {
+        Self::new()
     }
 }
-*/
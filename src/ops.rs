@@ -0,0 +1,188 @@
+//! The core bitboard API as a trait
+//!
+//! BitboardOps is implemented by the const sized bitboards, boxed or not, and by DynBitboard so
+//! algorithms can be written once for all of them. The set operations require both bitboards to
+//! have the same length.
+
+use crate::bulk;
+use crate::dynamic::BoardAllocator;
+use crate::{BitIterator, BitboardInternal, DynBitboard};
+
+/// The operations shared by all the bitboard flavors
+pub trait BitboardOps: Clone {
+    /// The iterator over the indices of the set bits returned by ones
+    type Ones<'a>: Iterator<Item = usize>
+    where
+        Self: 'a;
+
+    /// Returns the number of bits of the bitboard
+    fn bit_len(&self) -> usize;
+    /// Returns an empty bitboard of the same length
+    fn empty_like(&self) -> Self;
+
+    /// Sets the ith bit of the bitboard
+    fn set(&mut self, index: usize);
+    /// Unsets the ith bit of the bitboard
+    fn unset(&mut self, index: usize);
+    /// Returns wether or not the given bit is set
+    fn is_set(&self, index: usize) -> bool;
+    /// Returns wether no bit is set
+    fn is_empty(&self) -> bool;
+    /// Flips all the bits of the bitboard
+    fn flip(&mut self);
+    /// Returns an iterator over the indices of the set bits
+    fn ones(&self) -> Self::Ones<'_>;
+
+    /// Adds the bits of other
+    fn union_with(&mut self, other: &Self);
+    /// Keeps only the bits also set in other
+    fn intersect_with(&mut self, other: &Self);
+    /// Removes the bits of other
+    fn difference_with(&mut self, other: &Self);
+
+    /// Sets or unsets the ith bit of the bitboard depending on value
+    fn assign(&mut self, index: usize, value: bool) {
+        if value {
+            self.set(index);
+        } else {
+            self.unset(index);
+        }
+    }
+    /// Returns wether or not the given bit is unset
+    fn is_unset(&self, index: usize) -> bool {
+        !self.is_set(index)
+    }
+    /// Returns the number of set bits
+    fn count_ones(&self) -> usize {
+        self.ones().count()
+    }
+}
+
+impl<const N: usize, const R: usize> BitboardOps for BitboardInternal<N, R> {
+    type Ones<'a> = BitIterator<&'a [u64]>;
+
+    fn bit_len(&self) -> usize {
+        Self::BITS
+    }
+    fn empty_like(&self) -> Self {
+        Self::new()
+    }
+
+    fn set(&mut self, index: usize) {
+        BitboardInternal::set(self, index)
+    }
+    fn unset(&mut self, index: usize) {
+        BitboardInternal::unset(self, index)
+    }
+    fn is_set(&self, index: usize) -> bool {
+        BitboardInternal::is_set(self, index)
+    }
+    fn is_empty(&self) -> bool {
+        BitboardInternal::is_empty(self)
+    }
+    fn flip(&mut self) {
+        BitboardInternal::flip(self)
+    }
+    fn ones(&self) -> Self::Ones<'_> {
+        self.iter()
+    }
+    fn count_ones(&self) -> usize {
+        BitboardInternal::count_ones(self)
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        bulk::or_assign(&mut self.words, &other.words);
+    }
+    fn intersect_with(&mut self, other: &Self) {
+        bulk::and_assign(&mut self.words, &other.words);
+    }
+    fn difference_with(&mut self, other: &Self) {
+        bulk::and_not_assign(&mut self.words, &other.words);
+    }
+}
+
+impl<const N: usize, const R: usize> BitboardOps for Box<BitboardInternal<N, R>> {
+    type Ones<'a> = BitIterator<&'a [u64]>;
+
+    fn bit_len(&self) -> usize {
+        BitboardInternal::<N, R>::BITS
+    }
+    fn empty_like(&self) -> Self {
+        BitboardInternal::new_boxed()
+    }
+
+    fn set(&mut self, index: usize) {
+        BitboardOps::set(&mut **self, index)
+    }
+    fn unset(&mut self, index: usize) {
+        BitboardOps::unset(&mut **self, index)
+    }
+    fn is_set(&self, index: usize) -> bool {
+        BitboardOps::is_set(&**self, index)
+    }
+    fn is_empty(&self) -> bool {
+        BitboardOps::is_empty(&**self)
+    }
+    fn flip(&mut self) {
+        BitboardOps::flip(&mut **self)
+    }
+    fn ones(&self) -> Self::Ones<'_> {
+        BitboardOps::ones(&**self)
+    }
+    fn count_ones(&self) -> usize {
+        BitboardOps::count_ones(&**self)
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        BitboardOps::union_with(&mut **self, other)
+    }
+    fn intersect_with(&mut self, other: &Self) {
+        BitboardOps::intersect_with(&mut **self, other)
+    }
+    fn difference_with(&mut self, other: &Self) {
+        BitboardOps::difference_with(&mut **self, other)
+    }
+}
+
+impl<A: BoardAllocator> BitboardOps for DynBitboard<A> {
+    type Ones<'a> = BitIterator<&'a [u64]> where A: 'a;
+
+    fn bit_len(&self) -> usize {
+        self.len()
+    }
+    fn empty_like(&self) -> Self {
+        DynBitboard::empty_like(self)
+    }
+
+    fn set(&mut self, index: usize) {
+        DynBitboard::set(self, index)
+    }
+    fn unset(&mut self, index: usize) {
+        DynBitboard::unset(self, index)
+    }
+    fn is_set(&self, index: usize) -> bool {
+        DynBitboard::is_set(self, index)
+    }
+    fn is_empty(&self) -> bool {
+        DynBitboard::is_empty(self)
+    }
+    fn flip(&mut self) {
+        DynBitboard::flip(self)
+    }
+    fn ones(&self) -> Self::Ones<'_> {
+        self.iter()
+    }
+    fn count_ones(&self) -> usize {
+        DynBitboard::count_ones(self)
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        *self |= other;
+    }
+    fn intersect_with(&mut self, other: &Self) {
+        *self &= other;
+    }
+    fn difference_with(&mut self, other: &Self) {
+        *self -= other;
+    }
+}
@@ -0,0 +1,100 @@
+//! Conversions from and to external formats numbering the bits from the most significant end
+//!
+//! A bitboard always numbers the bits from the least significant bit of its first word. Network
+//! bitmaps and image masks often start from the most significant bit of each byte or word
+//! instead, the conversions below take the order of the external data so the indices match.
+
+use crate::{BitboardInternal, DynBitboard};
+
+/// The numbering of the bits within a byte or a word
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BitOrder {
+    /// The bit 0 is the least significant bit, as in the bitboards
+    LsbFirst,
+    /// The bit 0 is the most significant bit
+    MsbFirst,
+}
+
+impl BitOrder {
+    #[inline]
+    fn word(self, word: u64) -> u64 {
+        match self {
+            BitOrder::LsbFirst => word,
+            BitOrder::MsbFirst => word.reverse_bits(),
+        }
+    }
+    #[inline]
+    fn byte(self, byte: u8) -> u8 {
+        match self {
+            BitOrder::LsbFirst => byte,
+            BitOrder::MsbFirst => byte.reverse_bits(),
+        }
+    }
+}
+
+/// Fills the bytes from the bits given by is_set
+fn bits_to_bytes<F: Fn(usize) -> bool>(bits: usize, order: BitOrder, is_set: F) -> Vec<u8> {
+    let mut bytes = vec![0; bits.div_ceil(8)];
+    for index in (0..bits).filter(|&index| is_set(index)) {
+        bytes[index / 8] |= 1 << (index % 8);
+    }
+    bytes.iter().map(|&byte| order.byte(byte)).collect()
+}
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Creates a bitboard from N words whose bits are numbered in the given order, the bit i
+    /// being in the word i / 64, the junk bits are dropped
+    pub fn from_ordered_words(words: &[u64], order: BitOrder) -> Self {
+        assert_eq!(words.len(), N, "The bitboard has {} words", N);
+        let mut board = Self::new();
+        for (word, &external) in board.words.iter_mut().zip(words) {
+            *word = order.word(external);
+        }
+        board.words[N - 1] &= Self::LAST_WORD_MASK;
+        board
+    }
+
+    /// Returns the words of the bitboard with their bits numbered in the given order, the junk
+    /// bits unset
+    pub fn to_ordered_words(&self, order: BitOrder) -> Vec<u64> {
+        let mut words = self.words.to_vec();
+        words[N - 1] &= Self::LAST_WORD_MASK;
+        words.into_iter().map(|word| order.word(word)).collect()
+    }
+
+    /// Creates a bitboard from bytes whose bits are numbered in the given order, the bit i
+    /// being in the byte i / 8, the bits past BITS in the last byte are dropped
+    pub fn from_ordered_bytes(bytes: &[u8], order: BitOrder) -> Self {
+        assert_eq!(bytes.len(), Self::BITS.div_ceil(8), "The bitboard has {} bits", Self::BITS);
+        let mut board = Self::new();
+        for (index, &byte) in bytes.iter().enumerate() {
+            board.words[index / 8] |= (order.byte(byte) as u64) << (index % 8 * 8);
+        }
+        board.words[N - 1] &= Self::LAST_WORD_MASK;
+        board
+    }
+
+    /// Returns the bytes of the bitboard with their bits numbered in the given order, the bits
+    /// past BITS in the last byte unset
+    pub fn to_ordered_bytes(&self, order: BitOrder) -> Vec<u8> {
+        bits_to_bytes(Self::BITS, order, |index| self.is_set(index))
+    }
+}
+
+impl DynBitboard {
+    /// Creates a bitboard of bits bits from bytes whose bits are numbered in the given order
+    pub fn from_ordered_bytes(bytes: &[u8], bits: usize, order: BitOrder) -> Self {
+        assert_eq!(bytes.len(), bits.div_ceil(8), "The bitboard has {} bits", bits);
+        let mut board = Self::new(bits);
+        for (index, &byte) in bytes.iter().enumerate() {
+            let start = index * 8;
+            board.set_field(start, 8.min(bits - start), order.byte(byte) as u64);
+        }
+        board
+    }
+
+    /// Returns the bytes of the bitboard with their bits numbered in the given order
+    pub fn to_ordered_bytes(&self, order: BitOrder) -> Vec<u8> {
+        bits_to_bytes(self.len(), order, |index| self.is_set(index))
+    }
+}
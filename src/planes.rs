@@ -0,0 +1,94 @@
+//! Bit planes of byte buffers
+//!
+//! The bit plane k of a byte buffer is the bitboard made of the bit k of every byte, the
+//! byte i giving the bit i. Bytes are processed 8 at a time with multiplication tricks.
+//!
+//! 64 words can be bit-sliced the same way with transpose_64x64: after it the word k holds the
+//! bit k of every word.
+
+use crate::BitboardInternal;
+
+const BYTE_LSBS: u64 = 0x0101_0101_0101_0101;
+
+/// Gathers the LSBs of the 8 bytes of a little endian word into a byte
+#[inline]
+fn gather_byte_lsbs(word: u64) -> u64 {
+    ((word & BYTE_LSBS).wrapping_mul(0x0102_0408_1020_4080)) >> 56
+}
+
+/// Spreads the 8 LSBs of a word into the LSBs of its 8 bytes, inverse of gather_byte_lsbs
+#[inline]
+fn spread_to_byte_lsbs(bits: u64) -> u64 {
+    let selected = ((bits & 0xff) * BYTE_LSBS) & 0x8040_2010_0804_0201;
+    // every byte is either 0 or a single bit, adding 0x7f never carries to the next byte
+    ((selected + 0x7f7f_7f7f_7f7f_7f7f) >> 7) & BYTE_LSBS
+}
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Returns the bit plane of the given bit of every byte
+    /// There can't be more bytes than valid bits
+    pub fn from_byte_plane(bytes: &[u8], bit: u8) -> Self {
+        assert!(bit < 8);
+        assert!(bytes.len() <= Self::BITS);
+
+        let mut result = Self::new();
+        for (index, chunk) in bytes.chunks(8).enumerate() {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            let word = u64::from_le_bytes(word) >> bit;
+            result.set_field(8 * index, chunk.len(), gather_byte_lsbs(word));
+        }
+        result
+    }
+
+    /// Writes the bitboard as the bit plane of the given bit of every byte, the other bits
+    /// of the bytes are left untouched
+    pub fn scatter_byte_plane(&self, bytes: &mut [u8], bit: u8) {
+        assert!(bit < 8);
+        assert!(bytes.len() <= Self::BITS);
+
+        for (index, chunk) in bytes.chunks_mut(8).enumerate() {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            let word = u64::from_le_bytes(word);
+
+            let plane = spread_to_byte_lsbs(self.get_field(8 * index, chunk.len())) << bit;
+            let word = (word & !(BYTE_LSBS << bit)) | plane;
+            chunk.copy_from_slice(&word.to_le_bytes()[..chunk.len()]);
+        }
+    }
+}
+
+/// Transposes in place the 64x64 bit matrix whose rows are the words, the bit j of the word i
+/// going to the bit i of the word j
+/// This is the classic recursive transpose, swapping blocks of 32x32 bits, then 16x16 and so on
+pub fn transpose_64x64(rows: &mut [u64; 64]) {
+    let mut width = 32;
+    let mut mask: u64 = 0x0000_0000_ffff_ffff;
+    while width != 0 {
+        // k goes over the rows with the bit width unset
+        let mut k = 0;
+        while k < 64 {
+            let swapped = ((rows[k] >> width) ^ rows[k + width]) & mask;
+            rows[k] ^= swapped << width;
+            rows[k + width] ^= swapped;
+            k = (k + width + 1) & !width;
+        }
+        width >>= 1;
+        mask ^= mask << width;
+    }
+}
+
+impl BitboardInternal<1, 63> {
+    /// Transposes 64 bitboards of 64 bits, see transpose_64x64, this goes both ways
+    pub fn transpose_all(boards: &mut [Self; 64]) {
+        let mut rows = [0; 64];
+        for (row, board) in rows.iter_mut().zip(boards.iter()) {
+            *row = board.words[0];
+        }
+        transpose_64x64(&mut rows);
+        for (board, &row) in boards.iter_mut().zip(rows.iter()) {
+            board.words[0] = row;
+        }
+    }
+}
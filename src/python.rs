@@ -0,0 +1,219 @@
+//! Python bindings
+//!
+//! The Bitboard class wraps a DynBitboard whose size is chosen at creation. It behaves as a
+//! sequence of booleans for indexing (len, b[i], b[i] = True) and as a set of the indices of
+//! its set bits for membership, iteration and the |, &, ^ and - operators. Its words are
+//! exported without copy through the buffer protocol, as little endian bytes.
+//!
+//! The module initialization function is PyInit_bitboards, an extension module only has to
+//! link this crate, built with cargo rustc --features python --crate-type cdylib.
+
+use crate::DynBitboard;
+use pyo3::exceptions::{PyBufferError, PyIndexError, PyValueError};
+use pyo3::ffi;
+use pyo3::prelude::*;
+use std::ffi::{c_int, c_void, CString};
+use std::ptr;
+
+/// A bitboard of a size chosen at creation
+#[pyclass(name = "Bitboard", skip_from_py_object)]
+#[derive(Clone)]
+pub struct PyBitboard {
+    board: DynBitboard,
+}
+
+/// Iterator over the indices of the set bits of a Bitboard
+#[pyclass]
+pub struct OnesIterator {
+    indices: std::vec::IntoIter<usize>,
+}
+
+#[pymethods]
+impl OnesIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<usize> {
+        self.indices.next()
+    }
+}
+
+impl PyBitboard {
+    /// Returns the bit index of a Python index, negative indices counting from the end
+    fn bit_index(&self, index: isize) -> PyResult<usize> {
+        let len = self.board.len();
+        let index = if index < 0 { index + len as isize } else { index };
+        if index < 0 || index as usize >= len {
+            return Err(PyIndexError::new_err("bitboard index out of range"));
+        }
+        Ok(index as usize)
+    }
+
+    fn ones(&self) -> Vec<usize> {
+        self.board.ones().collect()
+    }
+
+    /// Applies the in place operation op once the lengths are checked
+    fn combine<F: Fn(&mut DynBitboard, &DynBitboard)>(&mut self, other: &Self, op: F) -> PyResult<()> {
+        if self.board.len() != other.board.len() {
+            return Err(PyValueError::new_err("bitboards of different lengths"));
+        }
+        op(&mut self.board, &other.board);
+        Ok(())
+    }
+
+    fn combined<F: Fn(&mut DynBitboard, &DynBitboard)>(&self, other: &Self, op: F) -> PyResult<Self> {
+        let mut result = self.clone();
+        result.combine(other, op)?;
+        Ok(result)
+    }
+}
+
+#[pymethods]
+impl PyBitboard {
+    /// Creates a new empty bitboard of the given number of bits
+    #[new]
+    fn new(bits: usize) -> Self {
+        Self {
+            board: DynBitboard::new(bits),
+        }
+    }
+
+    fn __len__(&self) -> usize {
+        self.board.len()
+    }
+
+    fn __getitem__(&self, index: isize) -> PyResult<bool> {
+        Ok(self.board.is_set(self.bit_index(index)?))
+    }
+
+    fn __setitem__(&mut self, index: isize, value: bool) -> PyResult<()> {
+        let index = self.bit_index(index)?;
+        self.board.assign(index, value);
+        Ok(())
+    }
+
+    fn __contains__(&self, index: isize) -> bool {
+        index >= 0 && self.__getitem__(index).unwrap_or(false)
+    }
+
+    fn __iter__(&self) -> OnesIterator {
+        OnesIterator {
+            indices: self.ones().into_iter(),
+        }
+    }
+
+    fn __eq__(&self, other: PyRef<'_, Self>) -> bool {
+        self.board == other.board
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Bitboard({}, ones={:?})", self.board.len(), self.ones())
+    }
+
+    /// Sets the given bit
+    fn add(&mut self, index: isize) -> PyResult<()> {
+        self.__setitem__(index, true)
+    }
+
+    /// Unsets the given bit
+    fn discard(&mut self, index: isize) -> PyResult<()> {
+        self.__setitem__(index, false)
+    }
+
+    /// Returns the number of set bits
+    fn count(&self) -> usize {
+        self.board.count_ones()
+    }
+
+    fn __or__(&self, other: PyRef<'_, Self>) -> PyResult<Self> {
+        self.combined(&other, |a, b| *a |= b)
+    }
+
+    fn __and__(&self, other: PyRef<'_, Self>) -> PyResult<Self> {
+        self.combined(&other, |a, b| *a &= b)
+    }
+
+    fn __xor__(&self, other: PyRef<'_, Self>) -> PyResult<Self> {
+        self.combined(&other, |a, b| *a ^= b)
+    }
+
+    fn __sub__(&self, other: PyRef<'_, Self>) -> PyResult<Self> {
+        self.combined(&other, |a, b| *a -= b)
+    }
+
+    fn __ior__(&mut self, other: PyRef<'_, Self>) -> PyResult<()> {
+        self.combine(&other, |a, b| *a |= b)
+    }
+
+    fn __iand__(&mut self, other: PyRef<'_, Self>) -> PyResult<()> {
+        self.combine(&other, |a, b| *a &= b)
+    }
+
+    fn __ixor__(&mut self, other: PyRef<'_, Self>) -> PyResult<()> {
+        self.combine(&other, |a, b| *a ^= b)
+    }
+
+    fn __isub__(&mut self, other: PyRef<'_, Self>) -> PyResult<()> {
+        self.combine(&other, |a, b| *a -= b)
+    }
+
+    /// Exports the words as a read only buffer of little endian bytes
+    unsafe fn __getbuffer__(slf: Bound<'_, Self>, view: *mut ffi::Py_buffer, flags: c_int) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("view is null"));
+        }
+        if (flags & ffi::PyBUF_WRITABLE) == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("bitboard buffers are read only"));
+        }
+        if cfg!(target_endian = "big") {
+            return Err(PyBufferError::new_err("bitboard buffers need a little endian platform"));
+        }
+
+        let (buf, len) = {
+            let bitboard = slf.borrow();
+            let words = bitboard.board.as_words();
+            (words.as_ptr() as *mut c_void, (words.len() * 8) as isize)
+        };
+
+        // The words are owned by the object the view keeps alive, and there is no way to
+        // reallocate them from Python
+        (*view).obj = slf.into_any().into_ptr();
+        (*view).buf = buf;
+        (*view).len = len;
+        (*view).readonly = 1;
+        (*view).itemsize = 1;
+        (*view).format = if (flags & ffi::PyBUF_FORMAT) == ffi::PyBUF_FORMAT {
+            CString::new("B").unwrap().into_raw()
+        } else {
+            ptr::null_mut()
+        };
+        (*view).ndim = 1;
+        (*view).shape = if (flags & ffi::PyBUF_ND) == ffi::PyBUF_ND {
+            &mut (*view).len
+        } else {
+            ptr::null_mut()
+        };
+        (*view).strides = if (flags & ffi::PyBUF_STRIDES) == ffi::PyBUF_STRIDES {
+            &mut (*view).itemsize
+        } else {
+            ptr::null_mut()
+        };
+        (*view).suboffsets = ptr::null_mut();
+        (*view).internal = ptr::null_mut();
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, view: *mut ffi::Py_buffer) {
+        if !(*view).format.is_null() {
+            drop(CString::from_raw((*view).format));
+        }
+    }
+}
+
+/// The bitboards Python module
+#[pymodule]
+fn bitboards(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyBitboard>()
+}
@@ -0,0 +1,163 @@
+//! Text rendering and parsing of 2D boards
+//!
+//! As in the grid module, the board is seen as lines of line_size cells and a last incomplete
+//! line is ignored. Each character covers a block of cells, the cells past the edges of the
+//! grid being unset.
+//!
+//! The ASCII grids have a character per cell, '#' for a set cell and '.' for an unset one when
+//! emitted. When parsed, the characters of set_chars are set cells and any other character is
+//! an unset cell, the empty lines are skipped.
+
+use crate::{BitboardInternal, DynBitboard};
+use std::fmt;
+
+/// The dots of a braille character for the cells of a 2x4 block, indexed by [y][x]
+const BRAILLE_DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// The quadrant block characters, indexed by the mask of the set cells of a 2x2 block
+/// (1 top left, 2 top right, 4 bottom left, 8 bottom right)
+const QUADRANTS: [char; 16] = [
+    ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
+];
+
+/// The ways an ASCII grid can fail to be parsed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsciiGridError {
+    /// A line doesn't have as many cells as the first one, line is counted from 0 without the
+    /// empty lines
+    RaggedLine { line: usize, len: usize, expected: usize },
+    /// The grid has more cells than the bitboard has bits
+    TooManyCells { cells: usize, bits: usize },
+}
+
+impl fmt::Display for AsciiGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RaggedLine { line, len, expected } => {
+                write!(f, "line {} has {} cells instead of {}", line, len, expected)
+            }
+            Self::TooManyCells { cells, bits } => {
+                write!(f, "the grid has {} cells but the bitboard only {} bits", cells, bits)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AsciiGridError {}
+
+/// Checks that all the lines of the grid have the same size and returns the number of cells,
+/// set_cell is called with the index of each set cell
+fn parse_ascii_grid<F: FnMut(usize)>(s: &str, set_chars: &[char], mut set_cell: F) -> Result<usize, AsciiGridError> {
+    let mut line_size = None;
+    let mut cells = 0;
+    for (line_no, line) in s.lines().filter(|line| !line.is_empty()).enumerate() {
+        let len = line.chars().count();
+        let expected = *line_size.get_or_insert(len);
+        if len != expected {
+            return Err(AsciiGridError::RaggedLine { line: line_no, len, expected });
+        }
+        for (x, c) in line.chars().enumerate() {
+            if set_chars.contains(&c) {
+                set_cell(cells + x);
+            }
+        }
+        cells += len;
+    }
+    Ok(cells)
+}
+
+/// Emits cell_count / line_size lines of line_size cells
+fn emit_ascii_grid<F: Fn(usize) -> bool>(line_size: usize, cell_count: usize, is_set: F) -> String {
+    assert!(line_size > 0);
+    let line_count = cell_count / line_size;
+    let mut text = String::with_capacity(line_count * (line_size + 1));
+    for y in 0..line_count {
+        for x in 0..line_size {
+            text.push(if is_set(y * line_size + x) { '#' } else { '.' });
+        }
+        text.push('\n');
+    }
+    text
+}
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Parses an ASCII grid, the cells are read line by line from the first bit of the board
+    pub fn from_ascii_grid(s: &str, set_chars: &[char]) -> Result<Self, AsciiGridError> {
+        let mut board = Self::new();
+        let mut overflow = false;
+        let cells = parse_ascii_grid(s, set_chars, |index| {
+            if index < Self::BITS {
+                board.set(index);
+            } else {
+                overflow = true;
+            }
+        })?;
+        if overflow || cells > Self::BITS {
+            return Err(AsciiGridError::TooManyCells { cells, bits: Self::BITS });
+        }
+        Ok(board)
+    }
+
+    /// Emits the board as an ASCII grid of lines of line_size cells
+    pub fn to_ascii_grid(&self, line_size: usize) -> String {
+        emit_ascii_grid(line_size, Self::BITS, |index| self.is_set(index))
+    }
+
+    /// Renders the board with a braille character per block of 2x4 cells, one text line per
+    /// 4 lines of cells
+    pub fn to_braille(&self, line_size: usize) -> String {
+        self.render_blocks(line_size, 2, 4, |mask| {
+            let dots = (0..8)
+                .filter(|bit| mask >> bit & 1 == 1)
+                .map(|bit| BRAILLE_DOTS[bit / 2][bit % 2])
+                .sum::<u32>();
+            std::char::from_u32(0x2800 + dots).unwrap()
+        })
+    }
+
+    /// Renders the board with a quadrant block character per block of 2x2 cells, one text line
+    /// per 2 lines of cells
+    pub fn to_blocks(&self, line_size: usize) -> String {
+        self.render_blocks(line_size, 2, 2, |mask| QUADRANTS[mask as usize])
+    }
+
+    /// Renders the blocks of width x height cells, glyph gets the mask of the set cells of a
+    /// block, line by line
+    fn render_blocks<F: Fn(u32) -> char>(&self, line_size: usize, width: usize, height: usize, glyph: F) -> String {
+        let line_count = Self::line_count(line_size);
+        let mut text = String::new();
+        for block_y in (0..line_count).step_by(height) {
+            for block_x in (0..line_size).step_by(width) {
+                let mut mask = 0;
+                for dy in 0..height.min(line_count - block_y) {
+                    for dx in 0..width.min(line_size - block_x) {
+                        if self.is_set((block_y + dy) * line_size + block_x + dx) {
+                            mask |= 1 << (dy * width + dx);
+                        }
+                    }
+                }
+                text.push(glyph(mask));
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl DynBitboard {
+    /// Parses an ASCII grid in a bitboard of exactly as many bits as the grid has cells
+    pub fn from_ascii_grid(s: &str, set_chars: &[char]) -> Result<Self, AsciiGridError> {
+        let mut set_cells = Vec::new();
+        let cells = parse_ascii_grid(s, set_chars, |index| set_cells.push(index))?;
+        let mut board = Self::new(cells);
+        for index in set_cells {
+            board.set(index);
+        }
+        Ok(board)
+    }
+
+    /// Emits the board as an ASCII grid of lines of line_size cells
+    pub fn to_ascii_grid(&self, line_size: usize) -> String {
+        emit_ascii_grid(line_size, self.len(), |index| self.is_set(index))
+    }
+}
@@ -0,0 +1,87 @@
+//! Serde support, behind the serde feature
+//!
+//! A const sized bitboard is serialized as a tuple of its N words and a DynBitboard as its
+//! length followed by its words. The junk bits are unset when serializing and rejected when
+//! deserializing.
+
+use crate::{BitboardInternal, DynBitboard};
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeStruct, SerializeTuple, Serializer};
+use std::fmt;
+
+impl<const N: usize, const R: usize> Serialize for BitboardInternal<N, R> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(N)?;
+        for word in &self.words[..N - 1] {
+            tuple.serialize_element(word)?;
+        }
+        tuple.serialize_element(&(self.words[N - 1] & Self::LAST_WORD_MASK))?;
+        tuple.end()
+    }
+}
+
+struct BitboardVisitor<const N: usize, const R: usize>;
+
+impl<'de, const N: usize, const R: usize> Visitor<'de> for BitboardVisitor<N, R> {
+    type Value = BitboardInternal<N, R>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} words of a bitboard of {} bits", N, BitboardInternal::<N, R>::BITS)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut board = BitboardInternal::new();
+        for index in 0..N {
+            board.words[index] = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(index, &self))?;
+        }
+        if board.words[N - 1] & !BitboardInternal::<N, R>::LAST_WORD_MASK != 0 {
+            return Err(de::Error::custom("junk bits set in the last word"));
+        }
+        Ok(board)
+    }
+}
+
+impl<'de, const N: usize, const R: usize> Deserialize<'de> for BitboardInternal<N, R> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_tuple(N, BitboardVisitor)
+    }
+}
+
+impl Serialize for DynBitboard {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let words: Vec<u64> = (0..self.len().div_ceil(64))
+            .map(|index| self.get_field(index * 64, 64.min(self.len() - index * 64)))
+            .collect();
+        let mut state = serializer.serialize_struct("DynBitboard", 2)?;
+        state.serialize_field("len", &self.len())?;
+        state.serialize_field("words", &words)?;
+        state.end()
+    }
+}
+
+/// The serialized form of a DynBitboard
+#[derive(serde::Deserialize)]
+#[serde(rename = "DynBitboard")]
+struct DynBitboardFields {
+    len: usize,
+    words: Vec<u64>,
+}
+
+impl<'de> Deserialize<'de> for DynBitboard {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let DynBitboardFields { len, words } = DynBitboardFields::deserialize(deserializer)?;
+        if words.len() != len.div_ceil(64) {
+            return Err(de::Error::invalid_length(words.len(), &"a word per 64 bits"));
+        }
+        let mut board = DynBitboard::new(len);
+        for (index, &word) in words.iter().enumerate() {
+            let width = 64.min(len - index * 64);
+            if width < 64 && word >> width != 0 {
+                return Err(de::Error::custom("junk bits set in the last word"));
+            }
+            board.set_field(index * 64, width, word);
+        }
+        Ok(board)
+    }
+}
+
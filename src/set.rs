@@ -0,0 +1,150 @@
+//! Set relations between bitboards of the same size
+//!
+//! The queries stop at the first word answering them and never allocate. PartialOrd orders the
+//! bitboards by inclusion so a <= b means that a is a subset of b, two bitboards where neither
+//! contains the other are not comparable.
+//!
+//! As for the equality, DynBitboards of different sizes are in no relation: the queries return
+//! false and they are not comparable.
+
+use crate::dynamic::BoardAllocator;
+use crate::{BitboardInternal, DynBitboard};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Returns the pairs of words of both bitboards, the junk bits unset
+    #[inline]
+    fn word_pairs<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = (u64, u64)> + 'a {
+        self.iter_words().zip(other.iter_words())
+    }
+
+    /// Returns wether at least one bit is set in both bitboards
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.word_pairs(other).any(|(a, b)| a & b != 0)
+    }
+    /// Returns wether no bit is set in both bitboards
+    #[inline]
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        !self.intersects(other)
+    }
+    /// Returns wether all the bits of the bitboard are set in other
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.word_pairs(other).all(|(a, b)| a & !b == 0)
+    }
+    /// Returns wether all the bits of other are set in the bitboard
+    #[inline]
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns the bits set in either bitboard
+    #[inline]
+    pub fn union(&self, other: &Self) -> Self {
+        self | other
+    }
+    /// Returns the bits set in both bitboards
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Self {
+        self & other
+    }
+    /// Returns the bits of the bitboard not set in other
+    #[inline]
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = *self;
+        result -= other;
+        result
+    }
+}
+
+impl<A: BoardAllocator> DynBitboard<A> {
+    /// Returns the pairs of words of both bitboards, they have to be of the same size
+    #[inline]
+    fn word_pairs<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = (u64, u64)> + 'a {
+        debug_assert_eq!(self.len(), other.len());
+        // the junk bits are always unset
+        self.as_words().iter().copied().zip(other.as_words().iter().copied())
+    }
+
+    /// Returns wether at least one bit is set in both bitboards, false if their sizes differ
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.word_pairs(other).any(|(a, b)| a & b != 0)
+    }
+    /// Returns wether no bit is set in both bitboards, false if their sizes differ
+    #[inline]
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.len() == other.len() && !self.intersects(other)
+    }
+    /// Returns wether all the bits of the bitboard are set in other, false if their sizes differ
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.word_pairs(other).all(|(a, b)| a & !b == 0)
+    }
+    /// Returns wether all the bits of other are set in the bitboard, false if their sizes differ
+    #[inline]
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns the bits set in either bitboard
+    #[inline]
+    pub fn union(&self, other: &Self) -> Self {
+        self | other
+    }
+    /// Returns the bits set in both bitboards
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Self {
+        self & other
+    }
+    /// Returns the bits of the bitboard not set in other
+    #[inline]
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut result = self.clone();
+        result -= other;
+        result
+    }
+}
+
+/// Hash of the valid bits, consistent with the equality ignoring the junk bits
+impl<const N: usize, const R: usize> Hash for BitboardInternal<N, R> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for word in self.iter_words() {
+            word.hash(state);
+        }
+    }
+}
+
+/// Order by inclusion, Less for a strict subset and Greater for a strict superset
+impl<const N: usize, const R: usize> PartialOrd for BitboardInternal<N, R> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        inclusion_order(self.word_pairs(other))
+    }
+}
+
+/// Order by inclusion as for the const sized bitboards, bitboards of different sizes are not
+/// comparable
+impl<A: BoardAllocator> PartialOrd for DynBitboard<A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.len() != other.len() {
+            return None;
+        }
+        inclusion_order(self.word_pairs(other))
+    }
+}
+
+/// Returns the inclusion order of two bitboards given their pairs of words
+fn inclusion_order<I: Iterator<Item = (u64, u64)>>(word_pairs: I) -> Option<Ordering> {
+    let (mut subset, mut superset) = (true, true);
+    for (a, b) in word_pairs {
+        subset &= a & !b == 0;
+        superset &= b & !a == 0;
+        if !subset && !superset {
+            return None;
+        }
+    }
+    match (subset, superset) {
+        (true, true) => Some(Ordering::Equal),
+        (true, false) => Some(Ordering::Less),
+        (false, true) => Some(Ordering::Greater),
+        (false, false) => None,
+    }
+}
@@ -0,0 +1,102 @@
+//! Bit-parallel Shift-Or string matching
+//!
+//! The pattern is compiled into one mask bitboard per byte value, the bit i of a mask is unset
+//! if the pattern has this byte at position i. Matching then costs a shift and an union per
+//! input byte, whatever the length of the pattern.
+
+use crate::BitboardInternal;
+
+/// A Shift-Or matcher for patterns of at most M bytes
+/// M has to be different than 0
+pub type ShiftOrMatcher<const M: usize> = ShiftOr<{(M-1) / 64 + 1}, {(M-1) % 64}>;
+
+/// A compiled Shift-Or pattern, see ShiftOrMatcher
+#[derive(Clone)]
+pub struct ShiftOr<const N: usize, const R: usize> {
+    masks: Vec<BitboardInternal<N, R>>,
+    len: usize,
+}
+
+impl<const N: usize, const R: usize> ShiftOr<N, R> {
+    /// Compiles the given pattern into per-byte masks
+    /// The pattern can't be empty and has to fit in the bitboard
+    pub fn new(pattern: &[u8]) -> Self {
+        assert!(!pattern.is_empty());
+        assert!(pattern.len() <= BitboardInternal::<N, R>::BITS);
+
+        let mut full = BitboardInternal::new();
+        full.flip();
+
+        let mut masks = vec![full; 256];
+        for (index, &byte) in pattern.iter().enumerate() {
+            masks[byte as usize].unset(index);
+        }
+
+        Self {
+            masks,
+            len: pattern.len(),
+        }
+    }
+
+    /// Returns the length of the compiled pattern
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns wether the compiled pattern is empty, this is always false
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns an iterator over the start positions of all the (possibly overlapping)
+    /// occurences of the pattern in the given byte stream
+    pub fn find_iter<I: IntoIterator<Item = u8>>(&self, text: I) -> Matches<'_, I::IntoIter, N, R> {
+        let mut state = BitboardInternal::new();
+        state.flip();
+
+        Matches {
+            matcher: self,
+            text: text.into_iter(),
+            state,
+            position: 0,
+        }
+    }
+
+    /// Returns the start position of the first occurence of the pattern in the given text
+    pub fn find(&self, text: &[u8]) -> Option<usize> {
+        self.find_iter(text.iter().copied()).next()
+    }
+}
+
+/// Iterator over the matches of a Shift-Or pattern in a byte stream, see ShiftOr::find_iter
+pub struct Matches<'a, I, const N: usize, const R: usize> {
+    matcher: &'a ShiftOr<N, R>,
+    text: I,
+    // bit i is unset if the last i+1 bytes match the first i+1 bytes of the pattern
+    state: BitboardInternal<N, R>,
+    position: usize,
+}
+
+impl<'a, I: Iterator<Item = u8>, const N: usize, const R: usize> Iterator for Matches<'a, I, N, R> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let last = self.matcher.len - 1;
+        loop {
+            let byte = self.text.next()?;
+            self.position += 1;
+
+            self.state.shift_left_one();
+            let mask = &self.matcher.masks[byte as usize];
+            for (word, mask_word) in self.state.words.iter_mut().zip(mask.words.iter()) {
+                *word |= mask_word;
+            }
+
+            if self.state.is_unset(last) {
+                return Some(self.position - self.matcher.len);
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Streaming the bits of a bitboard from and to io
+//!
+//! The bits are stored as the canonical bytes of checksum64: the bit i is the bit i % 8 of the
+//! byte i / 8 and the junk bits of the last byte are unset. The bytes go through a fixed size
+//! buffer so the input is never held in memory twice.
+
+use crate::dynamic::BoardAllocator;
+use crate::{BitboardInternal, DynBitboard};
+use std::io::{self, Read, Write};
+
+/// The number of bytes read or written at once
+const CHUNK_BYTES: usize = 8192;
+
+/// Reads the canonical bytes of bits bits, giving set_word each word with its index
+fn read_words<Rd: Read, F: FnMut(usize, u64)>(mut reader: Rd, bits: usize, mut set_word: F) -> io::Result<()> {
+    let total_bytes = bits.div_ceil(8);
+    let mut buffer = [0; CHUNK_BYTES];
+    let mut offset = 0;
+    while offset < total_bytes {
+        let chunk = &mut buffer[..CHUNK_BYTES.min(total_bytes - offset)];
+        reader.read_exact(chunk)?;
+        // the chunks are made of whole words except the last one
+        for (index, bytes) in chunk.chunks(8).enumerate() {
+            let mut word_bytes = [0; 8];
+            word_bytes[..bytes.len()].copy_from_slice(bytes);
+            set_word(offset / 8 + index, u64::from_le_bytes(word_bytes));
+        }
+        offset += chunk.len();
+    }
+    Ok(())
+}
+
+/// Writes the canonical bytes of bits bits, get_word giving each word from its index
+fn write_words<W: Write, F: Fn(usize) -> u64>(mut writer: W, bits: usize, get_word: F) -> io::Result<()> {
+    let total_bytes = bits.div_ceil(8);
+    let mut buffer = [0; CHUNK_BYTES];
+    let mut offset = 0;
+    while offset < total_bytes {
+        let chunk_len = CHUNK_BYTES.min(total_bytes - offset);
+        for (index, bytes) in buffer[..chunk_len].chunks_mut(8).enumerate() {
+            let word_index = offset / 8 + index;
+            let mut word = get_word(word_index);
+            let valid_bits = bits - word_index * 64;
+            if valid_bits < 64 {
+                word &= (1 << valid_bits) - 1;
+            }
+            bytes.copy_from_slice(&word.to_le_bytes()[..bytes.len()]);
+        }
+        writer.write_all(&buffer[..chunk_len])?;
+        offset += chunk_len;
+    }
+    Ok(())
+}
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Reads a bitboard from its canonical bytes, directly on the heap
+    pub fn read_boxed_from<Rd: Read>(reader: Rd) -> io::Result<Box<Self>> {
+        let mut board = Self::new_boxed();
+        read_words(reader, Self::BITS, |index, word| board.words[index] = word)?;
+        board.words[N - 1] &= Self::LAST_WORD_MASK;
+        Ok(board)
+    }
+
+    /// Writes the canonical bytes of the bitboard
+    pub fn write_bits_to<W: Write>(&self, writer: W) -> io::Result<()> {
+        write_words(writer, Self::BITS, |index| self.words[index])
+    }
+}
+
+impl DynBitboard {
+    /// Reads a bitboard of bits bits from its canonical bytes
+    pub fn read_bits_from<Rd: Read>(reader: Rd, bits: usize) -> io::Result<Self> {
+        let mut board = Self::new(bits);
+        read_words(reader, bits, |index, word| {
+            let start = index * 64;
+            board.set_field(start, 64.min(bits - start), word);
+        })?;
+        Ok(board)
+    }
+}
+
+impl<A: BoardAllocator> DynBitboard<A> {
+    /// Writes the canonical bytes of the bitboard
+    pub fn write_bits_to<W: Write>(&self, writer: W) -> io::Result<()> {
+        write_words(writer, self.len(), |index| {
+            let start = index * 64;
+            self.get_field(start, 64.min(self.len() - start))
+        })
+    }
+}
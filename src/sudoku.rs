@@ -0,0 +1,209 @@
+//! Sudoku candidate masks
+//!
+//! Cells are indexed row by row from the top left corner. Candidates are stored digit by
+//! digit: the bits 81 * d to 81 * d + 80 form the plane of the cells where the digit d can
+//! still be placed. Digits are 0 based, the digit d stands for d + 1 on the grid.
+
+use crate::Bitboard;
+
+/// One bit per cell of the grid
+pub type Cells = Bitboard<81>;
+/// One bit per cell and digit, see the module documentation for the layout
+pub type Candidates = Bitboard<729>;
+
+/// Returns the index of the cell at the given row and column
+#[inline]
+pub fn cell_index(row: usize, column: usize) -> usize {
+    row * 9 + column
+}
+
+/// Returns the index of the box containing the given cell, boxes are indexed row by row
+#[inline]
+pub fn box_of(cell: usize) -> usize {
+    (cell / 27) * 3 + (cell % 9) / 3
+}
+
+/// Returns the index of the given candidate in a Candidates board
+#[inline]
+pub fn candidate_index(cell: usize, digit: usize) -> usize {
+    digit * 81 + cell
+}
+
+/// The 27 units of the grid: the 9 rows, then the 9 columns and the 9 boxes
+const UNITS: [Cells; 27] = units();
+
+/// Returns the cells of the given row
+pub const fn row_mask(row: usize) -> Cells {
+    let mut cells = Cells::new();
+    let mut column = 0;
+    while column < 9 {
+        cells = cells.with_bit(row * 9 + column);
+        column += 1;
+    }
+    cells
+}
+
+/// Returns the cells of the given column
+pub const fn column_mask(column: usize) -> Cells {
+    let mut cells = Cells::new();
+    let mut row = 0;
+    while row < 9 {
+        cells = cells.with_bit(row * 9 + column);
+        row += 1;
+    }
+    cells
+}
+
+/// Returns the cells of the given box
+pub const fn box_mask(index: usize) -> Cells {
+    let first = (index / 3) * 27 + (index % 3) * 3;
+    let mut cells = Cells::new();
+    let mut cell = 0;
+    while cell < 9 {
+        cells = cells.with_bit(first + (cell / 3) * 9 + cell % 3);
+        cell += 1;
+    }
+    cells
+}
+
+/// Returns the rows, the columns and the boxes, see UNITS
+const fn units() -> [Cells; 27] {
+    let mut units = [Cells::new(); 27];
+    let mut index = 0;
+    while index < 9 {
+        units[index] = row_mask(index);
+        units[9 + index] = column_mask(index);
+        units[18 + index] = box_mask(index);
+        index += 1;
+    }
+    units
+}
+
+/// Returns the cells sharing a row, a column or a box with the given cell, itself excluded
+pub fn peers(cell: usize) -> Cells {
+    let mut cells = row_mask(cell / 9);
+    cells += column_mask(cell % 9);
+    cells += box_mask(box_of(cell));
+    cells.unset(cell);
+    cells
+}
+
+/// Returns a board where every digit is a candidate of every cell
+pub fn all_candidates() -> Candidates {
+    !Candidates::new()
+}
+
+/// Parses a grid of 81 cells given row by row, digits 1 to 9 are placed and any other
+/// character is an empty cell
+/// Returns None if the grid doesn't have 81 cells or if two givens contradict each other
+pub fn from_grid(grid: &str) -> Option<Candidates> {
+    let cells: Vec<char> = grid.chars().filter(|c| !c.is_whitespace()).collect();
+    if cells.len() != 81 {
+        return None;
+    }
+
+    let mut candidates = all_candidates();
+    for (cell, c) in cells.into_iter().enumerate() {
+        if let Some(digit) = c.to_digit(10).filter(|&d| d > 0) {
+            let digit = digit as usize - 1;
+            if candidates.is_unset(candidate_index(cell, digit)) {
+                return None;
+            }
+            place(&mut candidates, cell, digit);
+        }
+    }
+    Some(candidates)
+}
+
+/// Returns the cells where the given digit is still a candidate
+#[inline]
+pub fn digit_plane(candidates: &Candidates, digit: usize) -> Cells {
+    let mut cells = Cells::new();
+    cells.set_field(0, 64, candidates.get_field(digit * 81, 64));
+    cells.set_field(64, 17, candidates.get_field(digit * 81 + 64, 17));
+    cells
+}
+
+/// Overwrites the plane of the given digit
+#[inline]
+pub fn set_digit_plane(candidates: &mut Candidates, digit: usize, cells: &Cells) {
+    candidates.set_field(digit * 81, 64, cells.get_field(0, 64));
+    candidates.set_field(digit * 81 + 64, 17, cells.get_field(64, 17));
+}
+
+/// Returns the digits that are still candidates of the given cell, as the 9 LSBs of a word
+pub fn cell_candidates(candidates: &Candidates, cell: usize) -> u16 {
+    (0..9)
+        .filter(|&digit| candidates.is_set(candidate_index(cell, digit)))
+        .fold(0, |digits, digit| digits | 1 << digit)
+}
+
+/// Places the digit in the cell: it becomes the only candidate of the cell and it is
+/// eliminated from all the peers of the cell
+pub fn place(candidates: &mut Candidates, cell: usize, digit: usize) {
+    for other in 0..9 {
+        candidates.unset(candidate_index(cell, other));
+    }
+    candidates.set(candidate_index(cell, digit));
+
+    let mut plane = digit_plane(candidates, digit);
+    plane -= peers(cell);
+    set_digit_plane(candidates, digit, &plane);
+}
+
+/// Returns the cells of the grid that have no candidate left, a non empty result means that
+/// the grid is contradictory
+pub fn empty_cells(candidates: &Candidates) -> Cells {
+    let mut any = Cells::new();
+    for digit in 0..9 {
+        any += digit_plane(candidates, digit);
+    }
+    !any
+}
+
+/// Returns the naked singles, that is the candidates which are the only one left in their cell
+pub fn naked_singles(candidates: &Candidates) -> Candidates {
+    // bit-sliced counting of the candidates of each cell, saturating at two
+    let mut once = [0u64; 2];
+    let mut twice = [0u64; 2];
+    for digit in 0..9 {
+        let plane = digit_plane(candidates, digit);
+        for word_index in 0..2 {
+            let word = plane.get_field(64 * word_index, 64);
+            twice[word_index] |= once[word_index] & word;
+            once[word_index] |= word;
+        }
+    }
+
+    let mut single = Cells::new();
+    for word_index in 0..2 {
+        single.set_field(64 * word_index, 64, once[word_index] & !twice[word_index]);
+    }
+
+    let several = !single;
+
+    let mut singles = Candidates::new();
+    for digit in 0..9 {
+        let mut plane = digit_plane(candidates, digit);
+        plane -= several;
+        set_digit_plane(&mut singles, digit, &plane);
+    }
+    singles
+}
+
+/// Returns the hidden singles, that is the candidates which are the only place left for their
+/// digit in one of the units (row, column or box) of their cell
+pub fn hidden_singles(candidates: &Candidates) -> Candidates {
+    let mut singles = Candidates::new();
+    for digit in 0..9 {
+        let plane = digit_plane(candidates, digit);
+        for unit in UNITS.iter() {
+            let inside = plane & *unit;
+            if inside.count_ones() == 1 {
+                let cell = inside.first_set().unwrap();
+                singles.set(candidate_index(cell, digit));
+            }
+        }
+    }
+    singles
+}
@@ -0,0 +1,147 @@
+//! Two-level bitboards for huge sparse sets
+//!
+//! A summary keeps one bit per word of the bitboard, set if the word is not empty, so that
+//! the queries only visit the non empty words.
+
+use crate::{word_ones, BitboardInternal};
+
+/// A summarized bitboard of L bits
+/// L has to be different than 0
+pub type SummaryBitboard<const L: usize> = SummaryBitboardInternal<{(L-1) / 64 + 1}, {(L-1) % 64}>;
+
+/// Internal structure for SummaryBitboard, N and R are the ones of the underlying bitboard
+pub struct SummaryBitboardInternal<const N: usize, const R: usize> {
+    board: Box<BitboardInternal<N, R>>,
+    // bit k is set if the word k of the board is not empty
+    summary: Vec<u64>,
+}
+
+impl<const N: usize, const R: usize> SummaryBitboardInternal<N, R> {
+    /// Creates a new empty summarized bitboard
+    pub fn new() -> Self {
+        Self {
+            board: BitboardInternal::new_boxed(),
+            summary: vec![0; N.div_ceil(64)],
+        }
+    }
+
+    /// Builds the summary of an existing bitboard, its junk bits are unset
+    pub fn from_bitboard(board: Box<BitboardInternal<N, R>>) -> Self {
+        let mut result = Self {
+            board,
+            summary: vec![0; N.div_ceil(64)],
+        };
+        result.rebuild_summary();
+        result
+    }
+
+    /// Returns the underlying bitboard
+    #[inline]
+    pub fn as_bitboard(&self) -> &BitboardInternal<N, R> {
+        &self.board
+    }
+
+    /// Returns the underlying bitboard, dropping the summary
+    #[inline]
+    pub fn into_bitboard(self) -> Box<BitboardInternal<N, R>> {
+        self.board
+    }
+
+    /// Gives a mutable access to the whole underlying bitboard, the summary is rebuilt
+    /// afterward so this costs O(words)
+    pub fn modify<F: FnOnce(&mut BitboardInternal<N, R>)>(&mut self, f: F) {
+        f(&mut self.board);
+        self.rebuild_summary();
+    }
+
+    /// Sets the ith bit, the junk bits of the last word are not kept
+    #[inline]
+    pub fn set(&mut self, index: usize) {
+        self.board.set(index);
+        self.board.words[N - 1] &= BitboardInternal::<N, R>::LAST_WORD_MASK;
+        let word_index = index / 64;
+        if self.board.words[word_index] != 0 {
+            self.summary[word_index / 64] |= 1 << (word_index % 64);
+        }
+    }
+
+    /// Unsets the ith bit
+    #[inline]
+    pub fn unset(&mut self, index: usize) {
+        self.board.unset(index);
+        let word_index = index / 64;
+        if self.board.words[word_index] == 0 {
+            self.summary[word_index / 64] &= !(1 << (word_index % 64));
+        }
+    }
+
+    /// Returns wether or not the given bit is set
+    #[inline]
+    pub fn is_set(&self, index: usize) -> bool {
+        self.board.is_set(index)
+    }
+
+    /// Returns wether the bitboard is empty, in O(words / 64)
+    pub fn is_empty(&self) -> bool {
+        self.summary.iter().all(|&s| s == 0)
+    }
+
+    /// Returns the number of set bits, only the non empty words are visited
+    pub fn count_ones(&self) -> usize {
+        self.non_empty_words().map(|word_index| self.board.words[word_index].count_ones() as usize).sum()
+    }
+
+    /// Returns the index of the first set bit, if any
+    pub fn first_set(&self) -> Option<usize> {
+        let word_index = self.non_empty_words().next()?;
+        Some(word_index * 64 + self.board.words[word_index].trailing_zeros() as usize)
+    }
+
+    /// Returns the number of bytes allocated on the heap, the unused capacity included
+    pub fn heap_size_bytes(&self) -> usize {
+        std::mem::size_of::<BitboardInternal<N, R>>() + self.summary.capacity() * 8
+    }
+
+    /// Releases the unused capacity
+    pub fn shrink_to_fit(&mut self) {
+        self.summary.shrink_to_fit();
+    }
+
+    /// Returns the proportion of set bits
+    pub fn density(&self) -> f64 {
+        self.count_ones() as f64 / BitboardInternal::<N, R>::BITS as f64
+    }
+
+    /// Returns an iterator over the indices of the set bits, in increasing order
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.non_empty_words()
+            .flat_map(move |word_index| word_ones(self.board.words[word_index], word_index * 64))
+    }
+
+    /// Returns an iterator over the indices of the non empty words
+    fn non_empty_words(&self) -> impl Iterator<Item = usize> + '_ {
+        self.summary
+            .iter()
+            .enumerate()
+            .flat_map(|(summary_index, &summary_word)| word_ones(summary_word, summary_index * 64))
+    }
+
+    /// Unsets the junk bits of the board and recomputes the summary from scratch
+    fn rebuild_summary(&mut self) {
+        self.board.words[N - 1] &= BitboardInternal::<N, R>::LAST_WORD_MASK;
+        for summary_word in self.summary.iter_mut() {
+            *summary_word = 0;
+        }
+        for (word_index, &word) in self.board.words.iter().enumerate() {
+            if word != 0 {
+                self.summary[word_index / 64] |= 1 << (word_index % 64);
+            }
+        }
+    }
+}
+
+impl<const N: usize, const R: usize> Default for SummaryBitboardInternal<N, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,122 @@
+//! Symmetries of square boards
+//!
+//! A square board of side s stores the cell (x, y) at the index y * s + x.
+
+use crate::BitboardInternal;
+use std::cmp::Ordering;
+
+/// The 8 symmetries of the square (the dihedral group D4), in terms of cell coordinates
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum D4Transform {
+    /// (x, y) -> (x, y)
+    Identity,
+    /// (x, y) -> (s - 1 - y, x)
+    Rotate90,
+    /// (x, y) -> (s - 1 - x, s - 1 - y)
+    Rotate180,
+    /// (x, y) -> (y, s - 1 - x)
+    Rotate270,
+    /// (x, y) -> (s - 1 - x, y)
+    FlipX,
+    /// (x, y) -> (x, s - 1 - y)
+    FlipY,
+    /// (x, y) -> (y, x)
+    Transpose,
+    /// (x, y) -> (s - 1 - y, s - 1 - x)
+    AntiTranspose,
+}
+
+impl D4Transform {
+    /// All the transforms, the identity first
+    pub const ALL: [D4Transform; 8] = [
+        D4Transform::Identity,
+        D4Transform::Rotate90,
+        D4Transform::Rotate180,
+        D4Transform::Rotate270,
+        D4Transform::FlipX,
+        D4Transform::FlipY,
+        D4Transform::Transpose,
+        D4Transform::AntiTranspose,
+    ];
+
+    /// Returns the transform undoing this one
+    pub fn inverse(self) -> Self {
+        match self {
+            D4Transform::Rotate90 => D4Transform::Rotate270,
+            D4Transform::Rotate270 => D4Transform::Rotate90,
+            other => other,
+        }
+    }
+
+    /// Returns the image of the given cell index on a square board of the given side
+    pub fn map_index(self, index: usize, side: usize) -> usize {
+        let (x, y) = (index % side, index / side);
+        let last = side - 1;
+        let (x, y) = match self {
+            D4Transform::Identity => (x, y),
+            D4Transform::Rotate90 => (last - y, x),
+            D4Transform::Rotate180 => (last - x, last - y),
+            D4Transform::Rotate270 => (y, last - x),
+            D4Transform::FlipX => (last - x, y),
+            D4Transform::FlipY => (x, last - y),
+            D4Transform::Transpose => (y, x),
+            D4Transform::AntiTranspose => (last - y, last - x),
+        };
+        y * side + x
+    }
+}
+
+impl<const N: usize, const R: usize> BitboardInternal<N, R> {
+    /// Returns the side of the bitboard seen as a square board
+    /// The number of valid bits has to be a perfect square
+    fn square_side() -> usize {
+        let side = (0..=Self::BITS).find(|side| side * side >= Self::BITS).unwrap();
+        assert_eq!(side * side, Self::BITS, "the bitboard is not a square board");
+        side
+    }
+
+    /// Returns the image of the bitboard, seen as a square board, by the given transform
+    pub fn transform_d4(&self, transform: D4Transform) -> Self {
+        let side = Self::square_side();
+
+        let mut result = Self::new();
+        for index in self.iter() {
+            result.set(transform.map_index(index, side));
+        }
+        result
+    }
+
+    /// Returns the smallest of the 8 images of the bitboard, seen as a square board, by the
+    /// symmetries of the square, along with the transform giving it
+    /// Bitboards are compared as sequences of bits from the index 0, an unset bit being
+    /// smaller than a set one
+    pub fn canonical_under_d4(&self) -> (Self, D4Transform) {
+        let mut identity = *self;
+        // junk bits are not part of the board
+        identity.words[N - 1] &= Self::LAST_WORD_MASK;
+        let mut best = (identity, D4Transform::Identity);
+        for &transform in D4Transform::ALL[1..].iter() {
+            let image = self.transform_d4(transform);
+            if image.bit_sequence_cmp(&best.0) == Ordering::Less {
+                best = (image, transform);
+            }
+        }
+        best
+    }
+
+    /// Compares two bitboards as sequences of bits from the index 0, junk bits excluded
+    fn bit_sequence_cmp(&self, other: &Self) -> Ordering {
+        for (word, other_word) in self.iter_words().zip(other.iter_words()) {
+            let difference = word ^ other_word;
+            if difference != 0 {
+                let first_difference = difference & difference.wrapping_neg();
+                return if word & first_difference == 0 {
+                    Ordering::Less
+                } else {
+                    Ordering::Greater
+                };
+            }
+        }
+        Ordering::Equal
+    }
+}
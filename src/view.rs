@@ -0,0 +1,245 @@
+//! Views on a range of bits of a bitboard
+//!
+//! Views index their bits from the start of their range, they only borrow the words of the
+//! bitboard and are not tied to its size.
+//!
+//! Mutable views can be split anywhere, so two views may share a word. They access the words
+//! through atomics: the words shared with another view are modified with atomic read-modify-write
+//! operations on the bits of the view only, the other words with plain relaxed loads and stores.
+//! Mutable views of one bitboard can then be sent to different threads.
+
+use crate::{field_mask, read_field, word_ones};
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// A read only view on a contiguous range of bits, see BitboardInternal::view
+#[derive(Clone, Copy)]
+pub struct BitView<'a> {
+    words: &'a [u64],
+    offset: usize,
+    len: usize,
+}
+
+impl<'a> BitView<'a> {
+    pub(crate) fn new(words: &'a [u64], offset: usize, len: usize) -> Self {
+        debug_assert!(offset + len <= words.len() * 64);
+        Self { words, offset, len }
+    }
+
+    /// Returns the number of bits of the view
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns wether the view has no bits
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns wether or not the given bit of the view is set
+    #[inline]
+    pub fn is_set(&self, index: usize) -> bool {
+        assert!(index < self.len);
+        let index = self.offset + index;
+        self.words[index / 64] & (1 << (index % 64)) != 0
+    }
+
+    /// Returns wether or not the given bit of the view is unset
+    #[inline]
+    pub fn is_unset(&self, index: usize) -> bool {
+        !self.is_set(index)
+    }
+
+    /// Returns the width bits of the view starting at the given index as the LSBs of a word
+    #[inline]
+    pub fn get_field(&self, index: usize, width: usize) -> u64 {
+        assert!(index + width <= self.len);
+        read_field(self.words, self.offset + index, width)
+    }
+
+    /// Returns the view of a range of this view
+    pub fn subview(&self, range: Range<usize>) -> BitView<'a> {
+        assert!(range.start <= range.end && range.end <= self.len);
+        BitView::new(self.words, self.offset + range.start, range.end - range.start)
+    }
+
+    /// Returns wether all the bits of the view are unset
+    pub fn all_unset(&self) -> bool {
+        self.chunks().all(|(_, chunk)| chunk == 0)
+    }
+
+    /// Returns the number of set bits in the view
+    pub fn count_ones(&self) -> usize {
+        self.chunks().map(|(_, chunk)| chunk.count_ones() as usize).sum()
+    }
+
+    /// Returns the index of the first set bit of the view, if any
+    pub fn first_set(&self) -> Option<usize> {
+        self.ones().next()
+    }
+
+    /// Returns an iterator over the indices of the set bits of the view, in increasing order
+    pub fn ones(&self) -> impl Iterator<Item = usize> + 'a {
+        self.chunks().flat_map(|(index, chunk)| word_ones(chunk, index))
+    }
+
+    /// Returns an iterator over the view cut in 64 bits chunks, along with their index in the
+    /// view, the last chunk may be shorter
+    fn chunks(&self) -> impl Iterator<Item = (usize, u64)> + 'a {
+        let view = *self;
+        (0..self.len).step_by(64).map(move |index| (index, view.get_field(index, 64.min(view.len - index))))
+    }
+}
+
+/// A mutable view on a contiguous range of bits, see BitboardInternal::view_mut
+pub struct BitViewMut<'a> {
+    words: &'a [AtomicU64],
+    offset: usize,
+    len: usize,
+}
+
+impl<'a> BitViewMut<'a> {
+    pub(crate) fn new(words: &'a mut [u64], offset: usize, len: usize) -> Self {
+        debug_assert!(offset + len <= words.len() * 64);
+        assert_eq!(std::mem::align_of::<AtomicU64>(), std::mem::align_of::<u64>());
+        // This is sound because the words are borrowed mutably and AtomicU64 has the same
+        // layout as u64
+        let words = unsafe { &*(words as *mut [u64] as *const [AtomicU64]) };
+        Self { words, offset, len }
+    }
+
+    /// Returns the number of bits of the view
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns wether the view has no bits
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Splits the view in two at the given index, the first view gets the bits before mid
+    pub fn split_at_mut(self, mid: usize) -> (BitViewMut<'a>, BitViewMut<'a>) {
+        assert!(mid <= self.len);
+        let first = BitViewMut {
+            words: self.words,
+            offset: self.offset,
+            len: mid,
+        };
+        let second = BitViewMut {
+            words: self.words,
+            offset: self.offset + mid,
+            len: self.len - mid,
+        };
+        (first, second)
+    }
+
+    /// Returns wether or not the given bit of the view is set
+    #[inline]
+    pub fn is_set(&self, index: usize) -> bool {
+        self.get_field(index, 1) != 0
+    }
+
+    /// Returns wether or not the given bit of the view is unset
+    #[inline]
+    pub fn is_unset(&self, index: usize) -> bool {
+        !self.is_set(index)
+    }
+
+    /// Sets the given bit of the view
+    #[inline]
+    pub fn set(&mut self, index: usize) {
+        self.set_field(index, 1, 1);
+    }
+
+    /// Unsets the given bit of the view
+    #[inline]
+    pub fn unset(&mut self, index: usize) {
+        self.set_field(index, 1, 0);
+    }
+
+    /// Sets or unsets the given bit of the view depending on value
+    #[inline]
+    pub fn assign(&mut self, index: usize, value: bool) {
+        self.set_field(index, 1, value as u64);
+    }
+
+    /// Returns the width bits of the view starting at the given index as the LSBs of a word
+    pub fn get_field(&self, index: usize, width: usize) -> u64 {
+        assert!(width <= 64 && index + width <= self.len);
+        if width == 0 {
+            return 0;
+        }
+
+        let index = self.offset + index;
+        let (word_index, shift) = (index / 64, index % 64);
+        let mut value = self.load(word_index) >> shift;
+        if shift + width > 64 {
+            value |= self.load(word_index + 1) << (64 - shift);
+        }
+        value & field_mask(width)
+    }
+
+    /// Overwrites the width bits of the view starting at the given index with the LSBs of
+    /// value
+    pub fn set_field(&mut self, index: usize, width: usize, value: u64) {
+        assert!(width <= 64 && index + width <= self.len);
+        if width == 0 {
+            return;
+        }
+
+        let index = self.offset + index;
+        let (word_index, shift) = (index / 64, index % 64);
+        let mask = field_mask(width);
+        let value = value & mask;
+
+        self.write(word_index, mask << shift, value << shift);
+        if shift + width > 64 {
+            let written = 64 - shift;
+            self.write(word_index + 1, mask >> written, value >> written);
+        }
+    }
+
+    /// Sets or unsets all the bits of the view
+    pub fn fill(&mut self, value: bool) {
+        let value = if value { !0 } else { 0 };
+        for index in (0..self.len).step_by(64) {
+            self.set_field(index, 64.min(self.len - index), value);
+        }
+    }
+
+    /// Returns the number of set bits in the view
+    pub fn count_ones(&self) -> usize {
+        (0..self.len)
+            .step_by(64)
+            .map(|index| self.get_field(index, 64.min(self.len - index)).count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns wether the given word is entirely in the view, no other view can access it
+    #[inline]
+    fn owns_word(&self, word_index: usize) -> bool {
+        word_index * 64 >= self.offset && (word_index + 1) * 64 <= self.offset + self.len
+    }
+
+    #[inline]
+    fn load(&self, word_index: usize) -> u64 {
+        self.words[word_index].load(Ordering::Relaxed)
+    }
+
+    /// Overwrites the bits of the mask with the ones of value, the mask has to be in the view
+    #[inline]
+    fn write(&self, word_index: usize, mask: u64, value: u64) {
+        let word = &self.words[word_index];
+        if self.owns_word(word_index) {
+            word.store((word.load(Ordering::Relaxed) & !mask) | value, Ordering::Relaxed);
+        } else {
+            word.fetch_and(!mask | value, Ordering::Relaxed);
+            word.fetch_or(value, Ordering::Relaxed);
+        }
+    }
+}
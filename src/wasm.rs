@@ -0,0 +1,136 @@
+//! JavaScript bindings
+//!
+//! wasm-bindgen classes can't be generic, so a class is generated for a few common sizes.
+//! Words are exchanged as Uint32Array, the least significant half of a word first, and
+//! indices lists as Uint32Array too.
+
+use crate::Bitboard;
+use wasm_bindgen::prelude::*;
+
+macro_rules! wasm_bitboard {
+    ($name:ident, $bits:expr) => {
+        #[doc = concat!("A bitboard of ", stringify!($bits), " bits")]
+        #[wasm_bindgen]
+        #[derive(Clone)]
+        pub struct $name {
+            inner: Bitboard<$bits>,
+        }
+
+        #[wasm_bindgen]
+        impl $name {
+            /// Creates a new empty bitboard
+            #[wasm_bindgen(constructor)]
+            #[allow(clippy::new_without_default)]
+            pub fn new() -> Self {
+                Self {
+                    inner: Bitboard::<$bits>::new(),
+                }
+            }
+
+            /// Builds a bitboard from its 32 bits words, least significant first
+            #[wasm_bindgen(js_name = fromWords)]
+            pub fn from_words(words: &[u32]) -> Result<$name, JsError> {
+                if words.len() != $bits / 32 {
+                    return Err(JsError::new(concat!("expected ", stringify!($bits), " bits")));
+                }
+                let mut result = Self::new();
+                for (index, halves) in words.chunks(2).enumerate() {
+                    result.inner.words[index] = halves[0] as u64 | (halves[1] as u64) << 32;
+                }
+                Ok(result)
+            }
+
+            /// Returns the 32 bits words of the bitboard, least significant first
+            pub fn words(&self) -> Vec<u32> {
+                self.inner
+                    .words
+                    .iter()
+                    .flat_map(|&word| vec![word as u32, (word >> 32) as u32])
+                    .collect()
+            }
+
+            /// Number of bits of the bitboard
+            #[wasm_bindgen(getter)]
+            pub fn length(&self) -> usize {
+                $bits
+            }
+
+            /// Sets the given bit
+            pub fn set(&mut self, index: usize) -> Result<(), JsError> {
+                check_index(index, $bits)?;
+                self.inner.set(index);
+                Ok(())
+            }
+
+            /// Unsets the given bit
+            pub fn unset(&mut self, index: usize) -> Result<(), JsError> {
+                check_index(index, $bits)?;
+                self.inner.unset(index);
+                Ok(())
+            }
+
+            /// Returns wether the given bit is set
+            #[wasm_bindgen(js_name = isSet)]
+            pub fn is_set(&self, index: usize) -> bool {
+                index < $bits && self.inner.is_set(index)
+            }
+
+            /// Returns wether the bitboard is empty
+            #[wasm_bindgen(js_name = isEmpty)]
+            pub fn is_empty(&self) -> bool {
+                self.inner.is_empty()
+            }
+
+            /// Returns the number of set bits
+            #[wasm_bindgen(js_name = countOnes)]
+            pub fn count_ones(&self) -> usize {
+                self.inner.count_ones()
+            }
+
+            /// Returns the indices of the set bits, in increasing order
+            pub fn ones(&self) -> Vec<u32> {
+                self.inner.iter().map(|index| index as u32).collect()
+            }
+
+            /// Returns the union of both bitboards
+            pub fn union(&self, other: &$name) -> $name {
+                Self { inner: self.inner | other.inner }
+            }
+
+            /// Returns the intersection of both bitboards
+            pub fn intersection(&self, other: &$name) -> $name {
+                Self { inner: self.inner & other.inner }
+            }
+
+            /// Returns the bits of this bitboard that are not in the other one
+            pub fn difference(&self, other: &$name) -> $name {
+                Self { inner: self.inner.difference(&other.inner) }
+            }
+
+            /// Returns the bits set in exactly one of both bitboards
+            #[wasm_bindgen(js_name = symmetricDifference)]
+            pub fn symmetric_difference(&self, other: &$name) -> $name {
+                Self { inner: self.inner ^ other.inner }
+            }
+
+            /// Returns the complement of the bitboard
+            pub fn complement(&self) -> $name {
+                Self { inner: !self.inner }
+            }
+        }
+    };
+}
+
+fn check_index(index: usize, bits: usize) -> Result<(), JsError> {
+    if index < bits {
+        Ok(())
+    } else {
+        Err(JsError::new("index out of the bitboard"))
+    }
+}
+
+// These sizes are multiples of 64 so there are no junk bits to take care of
+wasm_bitboard!(Bitboard64, 64);
+wasm_bitboard!(Bitboard256, 256);
+wasm_bitboard!(Bitboard1024, 1024);
+wasm_bitboard!(Bitboard4096, 4096);
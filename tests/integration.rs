@@ -1,3 +1,5 @@
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
+
 use bitboards::*;
 
 /*
@@ -9,3 +11,1909 @@ fn print() {
     assert!(false);
 }
 */
+
+#[test]
+fn shift_or_short_pattern() {
+    let matcher = shift_or::ShiftOrMatcher::<8>::new(b"aba");
+    let matches: Vec<_> = matcher.find_iter(b"abababxaba".iter().copied()).collect();
+    assert_eq!(matches, vec![0, 2, 7]);
+    assert_eq!(matcher.find(b"xxxxab"), None);
+}
+
+#[test]
+fn shift_or_multi_word_pattern() {
+    let pattern: Vec<u8> = (0..150).map(|i| b'a' + (i % 7) as u8).collect();
+    let mut text = vec![b'z'; 40];
+    text.extend_from_slice(&pattern);
+    text.extend_from_slice(b"zz");
+
+    let matcher = shift_or::ShiftOrMatcher::<150>::new(&pattern);
+    assert_eq!(matcher.find(&text), Some(40));
+    assert_eq!(matcher.find(&text[41..]), None);
+}
+
+fn naive_edit_distance(a: &[u8], b: &[u8]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, &x) in a.iter().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &y) in b.iter().enumerate() {
+            let substitution = previous[j] + (x != y) as usize;
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn pseudo_random_bytes(seed: &mut u64, len: usize, alphabet: u8) -> Vec<u8> {
+    (0..len)
+        .map(|_| {
+            *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            b'a' + ((*seed >> 33) % alphabet as u64) as u8
+        })
+        .collect()
+}
+
+#[test]
+fn myers_distance_matches_naive() {
+    assert_eq!(edit_distance::MyersPattern::<16>::new(b"kitten").distance(b"sitting"), 3);
+
+    let mut seed = 42;
+    for &(m, n) in &[(1, 5), (63, 64), (64, 70), (65, 30), (150, 160), (200, 190)] {
+        let pattern = pseudo_random_bytes(&mut seed, m, 4);
+        let text = pseudo_random_bytes(&mut seed, n, 4);
+        let myers = edit_distance::MyersPattern::<200>::new(&pattern);
+        assert_eq!(myers.distance(&text), naive_edit_distance(&pattern, &text));
+    }
+}
+
+#[test]
+fn myers_banded_distance() {
+    let mut seed = 7;
+    for _ in 0..20 {
+        let pattern = pseudo_random_bytes(&mut seed, 150, 3);
+        let mut text = pattern.clone();
+        // a few random edits
+        for _ in 0..6 {
+            let position = (pseudo_random_bytes(&mut seed, 1, 26)[0] - b'a') as usize * 5;
+            text[position] = b'z';
+        }
+        text.drain(100..103);
+
+        let expected = naive_edit_distance(&pattern, &text);
+        let myers = edit_distance::MyersPattern::<150>::new(&pattern);
+        for max in 0..expected + 3 {
+            let result = myers.distance_within(&text, max);
+            if max < expected {
+                assert_eq!(result, None);
+            } else {
+                assert_eq!(result, Some(expected));
+            }
+        }
+    }
+}
+
+#[test]
+fn packed_fields_across_words() {
+    let mut bb = Bitboard::<180>::new();
+    bb.set_field(60, 10, 0b11_0110_1011);
+    assert_eq!(bb.get_field(60, 10), 0b11_0110_1011);
+    assert_eq!(bb.get_field(62, 4), 0b1010);
+    assert!(bb.is_set(60) && bb.is_unset(62) && bb.is_set(68));
+
+    bb.set_field(100, 64, !0);
+    bb.set_field(101, 3, 0);
+    assert_eq!(bb.get_field(100, 64), !0 ^ 0b1110);
+}
+
+#[test]
+fn dna_packing_and_kmers() {
+    let dna = dna::PackedDna::<40>::from_bases(b"ACGTtgcaACGTACGTACGTACGTACGTACGTACGTAC").unwrap();
+    assert_eq!(dna.len(), 38);
+    assert_eq!(dna.base(4), b'T');
+    assert_eq!(dna.kmer(0, 4), 0b11_10_01_00);
+    // the 32nd k-mer straddles a word boundary
+    assert_eq!(dna.kmer(30, 3), 0b00_11_10);
+    assert_eq!(dna.kmers(32).count(), 7);
+    assert_eq!(dna.kmers(32).nth(6), Some(dna.kmer(6, 32)));
+
+    assert!(dna::PackedDna::<4>::from_bases(b"ACNT").is_none());
+}
+
+#[test]
+fn dna_match_masks() {
+    let seq = dna::PackedDna::<40>::from_bases(b"AAAACCCCGGGGTTTTAAAACCCCGGGGTTTTAAAAC").unwrap();
+    let query = dna::PackedDna::<40>::from_bases(b"AAAACCCCGGGGTTTTAAAACCCCGGGGTTTTAAATT").unwrap();
+
+    let same: Bitboard<40> = seq.match_mask(&query);
+    for index in 0..40 {
+        assert_eq!(same.is_set(index), index < 35, "{}", index);
+    }
+
+    let adenines: Bitboard<40> = seq.base_mask(b'A');
+    for index in 0..40 {
+        assert_eq!(adenines.is_set(index), index < 37 && seq.base(index) == b'A');
+    }
+}
+
+#[test]
+fn sudoku_peer_masks() {
+    let peers = sudoku::peers(sudoku::cell_index(4, 4));
+    let count = (0..81).filter(|&cell| peers.is_set(cell)).count();
+    assert_eq!(count, 20);
+    assert!(peers.is_set(sudoku::cell_index(4, 0)));
+    assert!(peers.is_set(sudoku::cell_index(0, 4)));
+    assert!(peers.is_set(sudoku::cell_index(3, 5)));
+    assert!(peers.is_unset(sudoku::cell_index(4, 4)));
+    assert!(peers.is_unset(sudoku::cell_index(2, 2)));
+    assert_eq!(sudoku::box_of(sudoku::cell_index(8, 3)), 7);
+}
+
+#[test]
+fn sudoku_singles() {
+    let grid = "
+        8 . . | 1 2 3 | 4 5 6
+        . . . | . . . | . . .
+        . . . | . . . | . . .
+        . 4 . | . . . | . . .
+        . 5 . | . . . | . . .
+        . 6 . | . . . | . . .
+        . 7 . | . . . | . . .
+        . . . | . . . | . . .
+        . . . | . . . | . . .
+    ";
+    let grid: String = grid.chars().filter(|&c| c != '|').collect();
+    let candidates = sudoku::from_grid(&grid).unwrap();
+
+    // the cell (0, 1) can only be 9: 1 to 8 are in its row and column
+    let naked = sudoku::naked_singles(&candidates);
+    assert!(naked.is_set(sudoku::candidate_index(sudoku::cell_index(0, 1), 8)));
+    assert_eq!(sudoku::cell_candidates(&candidates, sudoku::cell_index(0, 2)), 0b1_0100_0000);
+
+    let hidden = sudoku::hidden_singles(&candidates);
+    assert!(hidden.is_set(sudoku::candidate_index(sudoku::cell_index(0, 2), 6)));
+    assert!(sudoku::empty_cells(&candidates).is_empty());
+
+    assert!(sudoku::from_grid(&format!("11{}", ".".repeat(79))).is_none());
+    assert!(sudoku::from_grid("123").is_none());
+}
+
+#[test]
+fn permute_matches_delta_swaps() {
+    // vertical flip of a 16x16 board
+    let perm: Vec<usize> = (0..256).map(|index| (15 - index / 16) * 16 + index % 16).collect();
+
+    let mut steps = Vec::new();
+    for &delta in &[128, 64, 32, 16] {
+        let mut mask = Bitboard::<256>::new();
+        for index in 0..256 {
+            if (index / delta) % 2 == 0 {
+                mask.set(index);
+            }
+        }
+        steps.push((mask, delta));
+    }
+
+    let mut bb = Bitboard::<256>::new();
+    for &index in &[0, 3, 17, 63, 64, 100, 130, 200, 255] {
+        bb.set(index);
+    }
+
+    let permuted = bb.permute(&perm);
+    bb.permute_delta_swaps(&steps);
+    for index in 0..256 {
+        assert_eq!(permuted.is_set(index), bb.is_set(index), "{}", index);
+    }
+    assert!(bb.is_set(15 * 16) && bb.is_set(15 * 16 + 3) && bb.is_set(15));
+}
+
+#[test]
+fn d4_canonical_form() {
+    use symmetry::D4Transform;
+
+    // an L shape in a corner of a 9x9 board
+    let mut bb = Bitboard::<81>::new();
+    for &index in &[80, 79, 78, 71] {
+        bb.set(index);
+    }
+
+    let (canonical, transform) = bb.canonical_under_d4();
+    // every image has the same canonical form
+    for &other in D4Transform::ALL.iter() {
+        let image = bb.transform_d4(other);
+        let (image_canonical, _) = image.canonical_under_d4();
+        for index in 0..81 {
+            assert_eq!(image_canonical.is_set(index), canonical.is_set(index));
+        }
+        let back = image.transform_d4(other.inverse());
+        assert!((0..81).all(|index| back.is_set(index) == bb.is_set(index)));
+    }
+
+    assert!(canonical.is_unset(0));
+    let image = bb.transform_d4(transform);
+    assert!((0..81).all(|index| image.is_set(index) == canonical.is_set(index)));
+
+    // a symmetric board stays its own canonical form whatever its junk bits, here bit 100
+    let center = Bitboard::<81>::from_words([1 << 40, 1 << 36]);
+    let (canonical, transform) = center.canonical_under_d4();
+    assert_eq!(transform, D4Transform::Identity);
+    assert_eq!(canonical, Bitboard::<81>::new().with_bit(40));
+}
+
+#[test]
+fn interleave_round_trip() {
+    let mut a = Bitboard::<100>::new();
+    let mut b = Bitboard::<100>::new();
+    for &index in &[0, 31, 32, 63, 64, 99] {
+        a.set(index);
+    }
+    for &index in &[1, 33, 70, 98] {
+        b.set(index);
+    }
+
+    let zipped: Bitboard<200> = a.interleave(&b);
+    for index in 0..100 {
+        assert_eq!(zipped.is_set(2 * index), a.is_set(index));
+        assert_eq!(zipped.is_set(2 * index + 1), b.is_set(index));
+    }
+
+    let (even, odd): (Bitboard<100>, Bitboard<100>) = zipped.deinterleave();
+    for index in 0..100 {
+        assert_eq!(even.is_set(index), a.is_set(index));
+        assert_eq!(odd.is_set(index), b.is_set(index));
+    }
+}
+
+#[test]
+fn reverse_and_swap_bytes() {
+    let mut bb = Bitboard::<100>::new();
+    for &index in &[0, 5, 63, 64, 90] {
+        bb.set(index);
+    }
+
+    let reversed = bb.reverse_bits();
+    for index in 0..100 {
+        assert_eq!(reversed.is_set(99 - index), bb.is_set(index));
+    }
+    assert!((100..128).all(|index| reversed.is_unset(index)));
+
+    let swapped = bb.swap_bytes_per_word();
+    assert!(swapped.is_set(56) && swapped.is_set(61) && swapped.is_set(7));
+    assert!(swapped.is_set(64 + 56) && swapped.is_set(64 + 32 + 2));
+}
+
+#[test]
+fn checksum_of_canonical_bytes() {
+    let mut bb = Bitboard::<72>::new();
+    for (index, &byte) in b"123456789".iter().enumerate() {
+        bb.set_field(8 * index, 8, byte as u64);
+    }
+    assert_eq!(bb.checksum64(), 0x995d_c9bb_df19_39fa);
+
+    // junk bits don't change the checksum
+    let mut a = Bitboard::<70>::new();
+    a.set(3);
+    let mut b = a;
+    b.set(100);
+    assert_eq!(a.checksum64(), b.checksum64());
+    b.set(69);
+    assert_ne!(a.checksum64(), b.checksum64());
+}
+
+#[test]
+fn summary_bitboard_stays_in_sync() {
+    let mut bb = summary::SummaryBitboard::<10_000_000>::new();
+    assert!(bb.is_empty());
+    assert_eq!(bb.first_set(), None);
+
+    for &index in &[9_999_999, 4_000_000, 4_000_001, 64 * 64 * 3] {
+        bb.set(index);
+    }
+    assert_eq!(bb.count_ones(), 4);
+    assert_eq!(bb.first_set(), Some(64 * 64 * 3));
+    assert_eq!(bb.ones().collect::<Vec<_>>(), vec![64 * 64 * 3, 4_000_000, 4_000_001, 9_999_999]);
+
+    bb.unset(64 * 64 * 3);
+    bb.unset(4_000_000);
+    assert_eq!(bb.first_set(), Some(4_000_001));
+
+    bb.modify(|board| board.unset(4_000_001));
+    assert_eq!(bb.ones().collect::<Vec<_>>(), vec![9_999_999]);
+}
+
+#[test]
+fn summary_bitboard_ignores_junk_bits() {
+    let mut board = Bitboard::<70>::new();
+    board.set(3);
+    // bit 100 is a junk bit of the last word
+    board.set(100);
+    assert_eq!(board.count_ones(), 1);
+
+    let mut bb = summary::SummaryBitboard::<70>::from_bitboard(Box::new(board));
+    assert_eq!(bb.count_ones(), 1);
+    assert_eq!(bb.ones().collect::<Vec<_>>(), vec![3]);
+
+    bb.modify(|board| board.set(127));
+    bb.set(90);
+    assert_eq!(bb.count_ones(), 1);
+    assert_eq!(bb.ones().collect::<Vec<_>>(), vec![3]);
+
+    bb.unset(3);
+    assert!(bb.is_empty());
+    assert_eq!(bb.first_set(), None);
+}
+
+#[test]
+fn hybrid_bitboard_set_operations() {
+    use std::collections::BTreeSet;
+
+    let mut a = hybrid::HybridBitboard::new();
+    let mut b = hybrid::HybridBitboard::new();
+    let mut expected_a = BTreeSet::new();
+    let mut expected_b = BTreeSet::new();
+
+    // a dense chunk, a sparse chunk and a far away bit
+    for index in (0..65536).step_by(3) {
+        a.set(index);
+        expected_a.insert(index);
+    }
+    for &index in &[70_000, 70_001, 1 << 30] {
+        a.set(index);
+        expected_a.insert(index);
+    }
+    for index in (0..70_010).step_by(5) {
+        b.set(index);
+        expected_b.insert(index);
+    }
+    assert_eq!(a.container_counts(), (2, 1));
+    assert_eq!(a.count_ones(), expected_a.len());
+
+    let mut union = a.clone();
+    union += b.clone();
+    assert!(union.ones().eq(expected_a.union(&expected_b).copied()));
+
+    let mut intersection = a.clone();
+    intersection.intersect_with(&b);
+    assert!(intersection.ones().eq(expected_a.intersection(&expected_b).copied()));
+
+    let mut difference = a.clone();
+    difference -= &b;
+    assert!(difference.ones().eq(expected_a.difference(&expected_b).copied()));
+    assert!((&a - &b).ones().eq(difference.ones()));
+
+    assert!((&a | &b).ones().eq(union.ones()));
+    assert!((&a & &b).ones().eq(intersection.ones()));
+    let symmetric_difference = &a ^ &b;
+    assert!(symmetric_difference.ones().eq(expected_a.symmetric_difference(&expected_b).copied()));
+    // the first chunk stays dense, the other two sparse
+    assert_eq!(symmetric_difference.container_counts(), (2, 1));
+    let mut nothing = a.clone();
+    nothing ^= &a;
+    assert!(nothing.is_empty());
+
+    // removing bits switches back to the sparse representation
+    for index in (0..60_000).step_by(3) {
+        a.unset(index);
+    }
+    assert_eq!(a.container_counts(), (3, 0));
+    a.unset(1 << 30);
+    assert!(a.is_unset(1 << 30));
+    assert_eq!(a.container_counts(), (2, 0));
+}
+
+#[test]
+fn compressed_bitboard_operations() {
+    let mut a = Bitboard::<10_000>::new();
+    let mut b = Bitboard::<10_000>::new();
+    for index in 1000..5000 {
+        a.set(index);
+    }
+    for &index in &[3, 4999, 5000, 9_999] {
+        a.set(index);
+    }
+    for index in (4000..9_999).step_by(7) {
+        b.set(index);
+    }
+
+    let ca = compressed::CompressedBitboard::<10_000>::from_bitboard(&a);
+    let cb = compressed::CompressedBitboard::<10_000>::from_bitboard(&b);
+    assert!(ca.compressed_words() < 10);
+    assert_eq!(ca.count_ones(), 4003);
+    assert!(ca.is_set(4999) && ca.is_set(9_999) && !ca.is_set(6000));
+
+    let expected = |op: fn(bool, bool) -> bool| -> Vec<usize> {
+        (0..10_000).filter(|&index| op(a.is_set(index), b.is_set(index))).collect()
+    };
+    assert_eq!((&ca & &cb).ones().collect::<Vec<_>>(), expected(|x, y| x && y));
+    assert_eq!((&ca | &cb).ones().collect::<Vec<_>>(), expected(|x, y| x || y));
+    assert_eq!((&ca ^ &cb).ones().collect::<Vec<_>>(), expected(|x, y| x != y));
+
+    let dense = (&ca | &cb).to_bitboard();
+    assert!((0..10_000).all(|index| dense.is_set(index) == (a.is_set(index) || b.is_set(index))));
+
+    // the junk bits stay cleared
+    let mut full = Bitboard::<10_000>::new();
+    full.flip();
+    let full = compressed::CompressedBitboard::<10_000>::from_bitboard(&full);
+    assert_eq!(full.count_ones(), 10_000);
+    assert_eq!((&full ^ &full), compressed::CompressedBitboard::<10_000>::new());
+    assert!((&full ^ &full).is_empty());
+    assert_eq!((&full | &ca).count_ones(), 10_000);
+}
+
+#[test]
+fn byte_planes_round_trip() {
+    let bytes: Vec<u8> = (0..77u32).map(|i| (i * 37 % 256) as u8).collect();
+
+    let mut rebuilt = vec![0u8; bytes.len()];
+    for bit in 0..8 {
+        let plane = Bitboard::<80>::from_byte_plane(&bytes, bit);
+        for (index, &byte) in bytes.iter().enumerate() {
+            assert_eq!(plane.is_set(index), byte & (1 << bit) != 0);
+        }
+        assert!((77..80).all(|index| plane.is_unset(index)));
+        plane.scatter_byte_plane(&mut rebuilt, bit);
+    }
+    assert_eq!(rebuilt, bytes);
+
+    // the other bits are preserved
+    let mut ones = vec![0xffu8; 10];
+    Bitboard::<10>::new().scatter_byte_plane(&mut ones, 3);
+    assert_eq!(ones, vec![0xf7; 10]);
+}
+
+#[test]
+fn gf2_polynomial_division() {
+    let polynomial = |exponents: &[usize]| {
+        let mut bb = Bitboard::<300>::new();
+        for &exponent in exponents {
+            bb.set(exponent);
+        }
+        bb
+    };
+
+    // x^3 + x + 1 divides x^7 + 1
+    let (quotient, remainder) = polynomial(&[7, 0]).div_rem(&polynomial(&[3, 1, 0]));
+    assert_eq!(quotient.degree(), Some(4));
+    assert!((0..300).all(|i| quotient.is_set(i) == [4, 2, 1, 0].contains(&i)));
+    assert!(remainder.is_empty());
+
+    // (x + 1)^2 = x^2 + 1 and the terms past x^299 are deleted
+    assert_eq!(polynomial(&[1, 0]).clmul(&polynomial(&[1, 0])), polynomial(&[2, 0]));
+    assert!(polynomial(&[299]).clmul(&polynomial(&[1])).is_empty());
+
+    // a = q * b + r with multi-word operands
+    let b = polynomial(&[130, 64, 63, 5, 0]);
+    let q = polynomial(&[150, 77, 3]);
+    let r = polynomial(&[129, 100, 1]);
+    let a = q.clmul(&b) ^ r;
+    assert_eq!(a.degree(), Some(280));
+    assert_eq!(b.clmul(&q), q.clmul(&b));
+    let (quotient, remainder) = a.div_rem(&b);
+    assert!((0..300).all(|i| quotient.is_set(i) == q.is_set(i)));
+    assert!((0..300).all(|i| remainder.is_set(i) == r.is_set(i)));
+    assert_eq!(Bitboard::<300>::new().degree(), None);
+}
+
+#[test]
+fn largest_rectangle() {
+    // a 70 cells wide grid so that lines straddle words
+    let mut bb = Bitboard::<{ 70 * 6 }>::new();
+    for y in 1..5 {
+        for x in 60..68 {
+            bb.set(y * 70 + x);
+        }
+    }
+    // a longer but thinner strip
+    for x in 0..20 {
+        bb.set(5 * 70 + x);
+    }
+    bb.set(0);
+
+    let rectangle = bb.largest_rectangle_of_ones(70).unwrap();
+    assert_eq!(rectangle, grid::Rectangle { x: 60, y: 1, width: 8, height: 4 });
+    assert_eq!(rectangle.area(), 32);
+    assert_eq!(Bitboard::<64>::new().largest_rectangle_of_ones(8), None);
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_handles() {
+    use bitboards::ffi::*;
+
+    unsafe {
+        let a = bitboard_new(100);
+        let b = bitboard_new(100);
+        assert!(bitboard_set(a, 3) && bitboard_set(a, 99) && !bitboard_set(a, 100));
+        assert!(bitboard_set(b, 99) && bitboard_set(b, 50));
+        assert!(bitboard_is_set(a, 99) && !bitboard_is_set(a, 1000));
+
+        assert!(bitboard_or(a, b));
+        assert_eq!(bitboard_count_ones(a), 3);
+        assert!(bitboard_and(a, b));
+        assert_eq!(bitboard_count_ones(a), 2);
+        assert!(bitboard_and(a, a) && bitboard_or(a, a));
+        assert_eq!(bitboard_count_ones(a), 2);
+
+        let c = bitboard_new(10);
+        assert!(!bitboard_or(a, c));
+        assert_eq!(bitboard_len(c), 10);
+        assert_eq!(bitboard_count_ones(std::ptr::null()), 0);
+
+        bitboard_free(a);
+        bitboard_free(b);
+        bitboard_free(c);
+    }
+}
+
+#[cfg(feature = "wasm")]
+#[test]
+fn wasm_wrapper() {
+    use bitboards::wasm::Bitboard256;
+
+    let mut a = Bitboard256::new();
+    a.set(3).unwrap();
+    a.set(200).unwrap();
+    let b = Bitboard256::from_words(&[0, 0, 1, 0, 0, 0, 0x100, 0]).unwrap();
+    assert_eq!(b.ones(), vec![64, 200]);
+
+    let words = a.union(&b).words();
+    assert_eq!(words.len(), 8);
+    assert_eq!(Bitboard256::from_words(&words).unwrap().ones(), vec![3, 64, 200]);
+    assert_eq!(a.intersection(&b).ones(), vec![200]);
+    assert_eq!(a.difference(&b).ones(), vec![3]);
+    assert_eq!(a.symmetric_difference(&b).count_ones(), 2);
+    assert_eq!(a.complement().count_ones(), 254);
+    assert!(!a.is_set(1000));
+}
+
+#[cfg(feature = "python")]
+#[test]
+fn python_bindings() {
+    use pyo3::prelude::*;
+    use pyo3::types::PyDict;
+
+    Python::initialize();
+    Python::attach(|py| {
+        let module = PyModule::new(py, "bitboards").unwrap();
+        module.add_class::<bitboards::python::PyBitboard>().unwrap();
+        let globals = PyDict::new(py);
+        globals.set_item("bitboards", module).unwrap();
+
+        let script = "
+a = bitboards.Bitboard(100)
+a[3] = True
+a[-1] = True
+a.add(64)
+b = bitboards.Bitboard(100)
+b.add(64)
+b.add(10)
+assert len(a) == 100 and a[99] and not a[4]
+assert 64 in a and 4 not in a and 1000 not in a
+assert list(a) == [3, 64, 99]
+assert list(a | b) == [3, 10, 64, 99]
+assert list(a & b) == [64] and list(a - b) == [3, 99] and (a ^ b).count() == 3
+a |= b
+assert a.count() == 4
+data = bytes(memoryview(a))
+assert len(data) == 16 and data[0] == 8 and data[8] == 1
+try:
+    a[100]
+    assert False
+except IndexError:
+    pass
+";
+        let script = std::ffi::CString::new(script).unwrap();
+        py.run(&script, Some(&globals), None).unwrap();
+    });
+}
+
+#[test]
+fn index_and_assign() {
+    let mut bb = Bitboard::<100>::new();
+    bb.assign(70, true);
+    bb.assign(3, true);
+    bb.assign(3, false);
+    assert!(bb[70]);
+    assert!(!bb[3]);
+    assert_eq!((0..100).filter(|&i| bb[i]).count(), 1);
+}
+
+#[test]
+fn bit_views() {
+    let mut bb = Bitboard::<200>::new();
+    for &index in &[5, 60, 70, 130, 199] {
+        bb.set(index);
+    }
+
+    let view = bb.view(50..150);
+    assert_eq!(view.len(), 100);
+    assert!(view.is_set(10) && view.is_unset(0));
+    assert_eq!(view.count_ones(), 3);
+    assert_eq!(view.ones().collect::<Vec<_>>(), vec![10, 20, 80]);
+    assert_eq!(view.first_set(), Some(10));
+
+    let sub = view.subview(15..100);
+    assert_eq!(sub.ones().collect::<Vec<_>>(), vec![5, 65]);
+    assert!(view.subview(21..80).all_unset());
+    assert!(bb.view(0..0).is_empty());
+}
+
+#[test]
+fn mutable_views_across_threads() {
+    let mut bb = Bitboard::<1000>::new();
+    {
+        let view = bb.view_mut(10..1000);
+        // the splits share words
+        let (first, rest) = view.split_at_mut(100);
+        let (second, third) = rest.split_at_mut(333);
+
+        std::thread::scope(|scope| {
+            for (mut part, step) in [(first, 2), (second, 3), (third, 5)] {
+                scope.spawn(move || {
+                    for index in (0..part.len()).step_by(step) {
+                        part.set(index);
+                    }
+                    part.unset(0);
+                });
+            }
+        });
+    }
+
+    for index in 0..1000 {
+        let expected = match index {
+            0..=9 => false,
+            10..=109 => (index - 10) % 2 == 0 && index != 10,
+            110..=442 => (index - 110) % 3 == 0 && index != 110,
+            _ => (index - 443) % 5 == 0 && index != 443,
+        };
+        assert_eq!(bb.is_set(index), expected, "{}", index);
+    }
+
+    let mut view = bb.view_mut(60..130);
+    view.fill(true);
+    assert_eq!(view.count_ones(), 70);
+    view.set_field(2, 10, 0);
+    assert_eq!(view.get_field(0, 16), 0b1111_0000_0000_0011);
+    assert!(bb.is_set(60) && bb.is_unset(62) && bb.is_unset(71) && bb.is_set(72));
+}
+
+#[test]
+fn ones_coordinates() {
+    let mut bb = Bitboard::<{ 70 * 3 + 5 }>::new();
+    for &index in &[0, 69, 71, 150, 212] {
+        bb.set(index);
+    }
+    // the cell 212 is in the incomplete last line
+    assert_eq!(bb.ones_coords(70).collect::<Vec<_>>(), vec![Coord(0, 0), Coord(69, 0), Coord(1, 1), Coord(10, 2)]);
+}
+
+#[cfg(feature = "chess")]
+#[test]
+fn chess_square_names() {
+    let mut bb = Bitboard::<64>::new();
+    for &square in &["e4", "a1", "h8", "d5"] {
+        bb.set(chess::square_index(square).unwrap());
+    }
+    assert_eq!(bb.ones_squares().collect::<Vec<_>>(), vec!["a1", "e4", "d5", "h8"]);
+    assert_eq!(chess::square_index("i1"), None);
+    assert_eq!(chess::square_name(8), "a2");
+}
+
+#[test]
+fn grid_shifts_do_not_wrap() {
+    let grid = Grid::new(10);
+    let mut bb = Bitboard::<105>::new();
+    bb.set(grid.index(9, 0));
+    bb.set(grid.index(0, 5));
+    bb.set(grid.index(3, 9));
+    // in the incomplete last line
+    bb.set(101);
+
+    let east = grid.shift(&bb, Direction::East);
+    assert_eq!(east.ones_coords(10).collect::<Vec<_>>(), vec![Coord(1, 5), Coord(4, 9)]);
+    let west = grid.shift(&bb, Direction::West);
+    assert_eq!(west.ones_coords(10).collect::<Vec<_>>(), vec![Coord(8, 0), Coord(2, 9)]);
+    assert!(grid.shift(&west, Direction::South).is_set(grid.index(8, 1)));
+    assert!(grid.shift(&bb, Direction::South).is_unset(101 + 10 - 100));
+    assert_eq!(grid.shift(&bb, Direction::NorthEast).ones_coords(10).collect::<Vec<_>>(), vec![Coord(1, 4), Coord(4, 8)]);
+
+    let mut center = Bitboard::<105>::new();
+    center.set(grid.index(5, 5));
+    assert_eq!(grid.dilate(&center, Connectivity::Four).ones_coords(10).count(), 5);
+    assert_eq!(grid.dilate(&center, Connectivity::Eight).ones_coords(10).count(), 9);
+}
+
+#[test]
+fn bfs_frontier_layers() {
+    // a wall with a single gap on a 8x8 board
+    let grid = Grid::new(8);
+    let mut passable = Bitboard::<64>::new();
+    passable.flip();
+    for y in 0..8 {
+        if y != 7 {
+            passable.unset(grid.index(4, y));
+        }
+    }
+
+    let mut start = Bitboard::<64>::new();
+    start.set(grid.index(0, 0));
+
+    let layers: Vec<_> = grid.frontier_layers(&start, &passable, Connectivity::Four).collect();
+    // the far corner is reached through the gap at (4, 7)
+    let corner = layers.iter().position(|layer| layer.is_set(grid.index(7, 0))).unwrap();
+    assert_eq!(corner, 4 + 7 + 3 + 7);
+    assert_eq!(layers.len(), corner + 1);
+    assert_eq!(layers[1].ones_coords(8).collect::<Vec<_>>(), vec![Coord(1, 0), Coord(0, 1)]);
+}
+
+#[test]
+fn grid_pathfinding() {
+    let grid = Grid::new(8);
+    let mut obstacles = Bitboard::<64>::new();
+    for y in 0..7 {
+        obstacles.set(grid.index(4, y));
+    }
+
+    let (path, length) = grid.find_path(&obstacles, grid.index(0, 0), grid.index(7, 0), Connectivity::Four).unwrap();
+    assert_eq!(length, 21);
+    assert_eq!(path.ones_coords(8).count(), 22);
+    assert!(path.is_set(grid.index(4, 7)));
+    // every cell of the path has a neighbor on the path
+    for index in (0..64).filter(|&index| path.is_set(index)) {
+        let mut cell = Bitboard::<64>::new();
+        cell.set(index);
+        let neighbors = grid.dilate(&cell, Connectivity::Four);
+        assert!((0..64).any(|other| other != index && neighbors.is_set(other) && path.is_set(other)));
+    }
+    let (_, diagonal_length) = grid.find_path(&obstacles, grid.index(0, 0), grid.index(7, 0), Connectivity::Eight).unwrap();
+    assert_eq!(diagonal_length, 14);
+
+    obstacles.set(grid.index(4, 7));
+    assert!(grid.find_path(&obstacles, grid.index(0, 0), grid.index(7, 0), Connectivity::Four).is_none());
+    assert!(grid.find_path(&obstacles, Coord(4, 0), Coord(0, 0), Connectivity::Four).is_none());
+    assert_eq!(grid.find_path(&obstacles, 0usize, 0usize, Connectivity::Four).unwrap().1, 0);
+}
+
+#[test]
+fn shadowcasting_field_of_view() {
+    let grid = Grid::new(9);
+    let open = Bitboard::<81>::new();
+    let visible = grid.field_of_view(&open, grid.index(4, 4));
+    assert_eq!(visible.ones_coords(9).count(), 81);
+
+    let mut pillar = Bitboard::<81>::new();
+    pillar.set(grid.index(4, 3));
+    let visible = grid.field_of_view(&pillar, Coord(4, 4));
+    assert!(visible.is_set(grid.index(4, 3)));
+    assert!(visible.is_unset(grid.index(4, 2)) && visible.is_unset(grid.index(4, 0)));
+    assert!(visible.is_set(grid.index(3, 2)) && visible.is_set(grid.index(0, 4)));
+
+    // symmetry between floor cells on a cluttered map
+    let mut seed = 3;
+    let mut blockers = Bitboard::<81>::new();
+    for byte in pseudo_random_bytes(&mut seed, 81, 4).into_iter().enumerate() {
+        if byte.1 == b'a' {
+            blockers.set(byte.0);
+        }
+    }
+    let views: Vec<_> = (0..81usize).map(|origin| grid.field_of_view(&blockers, origin)).collect();
+    for a in (0..81).filter(|&a| blockers.is_unset(a)) {
+        for b in (0..81).filter(|&b| blockers.is_unset(b)) {
+            assert_eq!(views[a].is_set(b), views[b].is_set(a), "{} {}", a, b);
+        }
+    }
+}
+
+#[test]
+fn grid_territories() {
+    let grid = Grid::new(7);
+    let mut passable = *grid.cells();
+    let mut left = Bitboard::<49>::new();
+    left.set(grid.index(0, 3));
+    let mut right = Bitboard::<49>::new();
+    right.set(grid.index(6, 3));
+    let seeds = [left, right];
+
+    let shared = grid.territories(&seeds, &passable, Connectivity::Four, Ties::Shared);
+    assert!(shared[0].is_set(grid.index(3, 0)) && shared[1].is_set(grid.index(3, 0)));
+    assert!(shared[0].is_set(grid.index(2, 6)) && shared[1].is_unset(grid.index(2, 6)));
+    let neutral = grid.territories(&seeds, &passable, Connectivity::Four, Ties::Neutral);
+    assert!(neutral[0].is_unset(grid.index(3, 5)) && neutral[1].is_unset(grid.index(3, 5)));
+    let first = grid.territories(&seeds, &passable, Connectivity::Four, Ties::FirstSeed);
+    assert!(first[0].is_set(grid.index(3, 5)) && first[1].is_unset(grid.index(3, 5)));
+    assert_eq!(first[0].ones_coords(7).count() + first[1].ones_coords(7).count(), 49);
+
+    // a wall with a gap near the right seed, which then gets the whole right side
+    for y in 0..6 {
+        passable.unset(grid.index(4, y));
+    }
+    let first = grid.territories(&seeds, &passable, Connectivity::Four, Ties::FirstSeed);
+    assert!(first[1].is_set(grid.index(5, 0)) && first[1].is_set(grid.index(4, 6)));
+    assert!(first[0].is_set(grid.index(3, 6)) && first[0].is_set(grid.index(3, 0)));
+    assert!(first[0].is_unset(grid.index(4, 2)) && first[1].is_unset(grid.index(4, 2)));
+}
+
+#[test]
+fn life_like_automaton() {
+    assert_eq!(Rule::parse("B3/S23"), Some(Rule::LIFE));
+    assert_eq!(Rule::parse("b36/s23"), Some(Rule::new(&[3, 6], &[2, 3])));
+    assert_eq!(Rule::parse("B9/S23"), None);
+    assert_eq!(Rule::parse("S23/B3"), None);
+
+    // a glider comes back shifted by (1, 1) after 4 generations
+    let grid = Grid::new(10);
+    let mut board = Bitboard::<100>::new();
+    for &(x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].iter() {
+        board.set(grid.index(x, y));
+    }
+    let mut glider = board;
+    for _ in 0..4 {
+        glider = grid.automaton_step(&glider, &Rule::LIFE);
+    }
+    let moved: Vec<_> = glider.ones_coords(10).collect();
+    let expected: Vec<_> = board.ones_coords(10).map(|Coord(x, y)| Coord(x + 1, y + 1)).collect();
+    assert_eq!(moved, expected);
+
+    // a blinker in a corner, its outer cells die as there is no wrap around
+    let mut blinker = Bitboard::<100>::new();
+    for x in 0..3 {
+        blinker.set(grid.index(x, 0));
+    }
+    let next = grid.automaton_step(&blinker, &Rule::LIFE);
+    assert_eq!(next.ones_coords(10).collect::<Vec<_>>(), vec![Coord(1, 0), Coord(1, 1)]);
+}
+
+#[test]
+fn grid_topologies() {
+    let torus = Grid::<1, 24>::with_topology(5, Topology::Toroidal);
+    let cylinder = Grid::<1, 24>::with_topology(5, Topology::Cylindrical);
+    assert_eq!(Grid::<1, 24>::new(5).topology(), Topology::Bounded);
+
+    let mut corner = Bitboard::<25>::new();
+    corner.set(0);
+    let moved = torus.shift(&corner, Direction::NorthWest);
+    assert_eq!(moved.ones_coords(5).collect::<Vec<_>>(), vec![Coord(4, 4)]);
+    assert!(cylinder.shift(&corner, Direction::North).is_empty());
+    let west = cylinder.shift(&corner, Direction::West);
+    assert_eq!(west.ones_coords(5).collect::<Vec<_>>(), vec![Coord(4, 0)]);
+    assert_eq!(torus.dilate(&corner, Connectivity::Eight).ones_coords(5).count(), 9);
+    assert_eq!(cylinder.dilate(&corner, Connectivity::Eight).ones_coords(5).count(), 6);
+
+    // a blinker across the vertical edge keeps blinking on the cylinder only
+    let mut blinker = Bitboard::<25>::new();
+    for &x in [4, 0, 1].iter() {
+        blinker.set(cylinder.index(x, 2));
+    }
+    let next = cylinder.automaton_step(&blinker, &Rule::LIFE);
+    assert_eq!(next.ones_coords(5).collect::<Vec<_>>(), vec![Coord(0, 1), Coord(0, 2), Coord(0, 3)]);
+    let bounded = Grid::<1, 24>::new(5).automaton_step(&blinker, &Rule::LIFE);
+    assert_eq!(bounded.ones_coords(5).count(), 0);
+}
+
+#[test]
+fn compact_rendering() {
+    // 5x5 with the diagonal and the last column
+    let mut board = Bitboard::<25>::new();
+    for i in 0..5 {
+        board.set(i * 5 + i);
+        board.set(i * 5 + 4);
+    }
+    assert_eq!(board.to_blocks(5), "▚ ▌\n ▚▌\n  ▘\n");
+    assert_eq!(board.to_braille(5), "⠑⢄⡇\n⠀⠀⠁\n");
+}
+
+#[test]
+fn adjacency_to_dot() {
+    let mut edges = Bitboard::<9>::new();
+    edges.set(1);
+    edges.set(3 + 2);
+    edges.set(2 * 3);
+    let dot = edges.to_dot(&["a", "b", "say \"c\""]);
+    assert_eq!(
+        dot,
+        "digraph {\n    0 [label=\"a\"];\n    1 [label=\"b\"];\n    2 [label=\"say \\\"c\\\"\"];\n    0 -> 1;\n    1 -> 2;\n    2 -> 0;\n}\n"
+    );
+}
+
+#[cfg(feature = "petgraph")]
+#[test]
+fn petgraph_conversions() {
+    use petgraph::graph::{NodeIndex, UnGraph};
+    use petgraph::visit::Dfs;
+
+    let mut edges = Bitboard::<16>::new();
+    edges.set(1);
+    edges.set(4 + 2);
+    let graph = edges.to_graph(4);
+    assert_eq!((graph.node_count(), graph.edge_count()), (4, 2));
+    let back = Bitboard::<16>::from_graph(&graph);
+    assert!(back.is_set(1) && back.is_set(6) && back.is_unset(4));
+    let map = edges.to_graph_map(4);
+    assert!(map.contains_edge(1, 2) && !map.contains_edge(2, 1));
+    assert!(Bitboard::<16>::from_graph_map(&map, 4).is_set(6));
+
+    let undirected = UnGraph::<(), ()>::from_edges([(0, 1)]);
+    let matrix = Bitboard::<4>::from_graph(&undirected);
+    assert!(matrix.is_set(1) && matrix.is_set(2));
+
+    // a depth first search with a bitboard as visited set
+    let mut dfs = Dfs::from_parts(vec![NodeIndex::new(0)], Bitboard::<4>::new());
+    let mut reached = Vec::new();
+    while let Some(node) = dfs.next(&graph) {
+        reached.push(node.index());
+    }
+    assert_eq!(reached, vec![0, 1, 2]);
+}
+
+#[test]
+fn incremental_components() {
+    let grid = Grid::new(5);
+    let mut tracker = components::ComponentTracker::<1, 24>::new(grid.clone(), Connectivity::Four);
+    for x in 0..5 {
+        tracker.set(Coord(x, 2));
+    }
+    tracker.set(grid.index(0, 0));
+    assert_eq!(tracker.component_count(), 2);
+    assert!(tracker.connected(grid.index(0, 2), grid.index(4, 2)));
+
+    // cutting the line in two, then sticking the corner to a half
+    tracker.unset(Coord(2, 2));
+    assert_eq!(tracker.component_count(), 3);
+    assert!(!tracker.connected(Coord(0, 2), Coord(4, 2)));
+    tracker.set(grid.index(0, 1));
+    assert_eq!(tracker.component_count(), 2);
+    let component = tracker.component(Coord(0, 0));
+    assert_eq!(component.ones_coords(5).collect::<Vec<_>>(), vec![Coord(0, 0), Coord(0, 1), Coord(0, 2), Coord(1, 2)]);
+    assert_eq!(tracker.label(Coord(2, 2)), None);
+
+    // the diagonal neighbors only count with the 8-connectivity
+    let mut diagonal = Bitboard::<25>::new();
+    diagonal.set(grid.index(0, 0));
+    diagonal.set(grid.index(1, 1));
+    let mut four = components::ComponentTracker::from_board(grid.clone(), Connectivity::Four, diagonal);
+    let mut eight = components::ComponentTracker::from_board(grid, Connectivity::Eight, diagonal);
+    assert_eq!((four.component_count(), eight.component_count()), (2, 1));
+}
+
+#[test]
+fn board_history_undo_redo() {
+    let mut history = history::BoardHistory::new(Bitboard::<1000>::new());
+    let mut board = Bitboard::<1000>::new();
+    for &index in [3, 500, 999].iter() {
+        board.set(index);
+        history.commit(&board);
+    }
+    assert_eq!((history.revision(), history.revision_count()), (3, 4));
+    // one changed word per commit
+    assert_eq!(history.delta_words(), 3);
+
+    assert!(history.undo() && history.undo());
+    assert!(history.current().is_set(3) && history.current().is_unset(500));
+    assert!(history.redo());
+    assert!(history.current().is_set(500) && history.current().is_unset(999));
+
+    history.jump_to(0);
+    assert!(history.current().is_empty() && !history.undo());
+    history.jump_to(3);
+    assert!(history.current().is_set(999) && !history.redo());
+
+    // committing after an undo drops the undone revisions
+    history.jump_to(1);
+    let mut branch = *history.current();
+    branch.unset(3);
+    assert_eq!(history.commit(&branch), 2);
+    assert_eq!(history.revision_count(), 3);
+    assert!(history.current().is_empty());
+}
+
+#[test]
+fn board_timeline_replay() {
+    let grid = Grid::new(16);
+    let mut board = Bitboard::<256>::new();
+    for &(x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].iter() {
+        board.set(grid.index(x, y));
+    }
+    let mut timeline = history::BoardTimeline::new(8);
+    assert!(timeline.is_empty() && timeline.get(0).is_none());
+    let mut generations = Vec::new();
+    for _ in 0..20 {
+        timeline.push(&board);
+        generations.push(board);
+        board = grid.automaton_step(&board, &Rule::LIFE);
+    }
+    assert_eq!(timeline.len(), 20);
+    assert!(timeline.get(20).is_none());
+    assert!(timeline.stored_words() < 20 * 4);
+
+    let same = |a: &Bitboard<256>, b: &Bitboard<256>| a.ones_coords(16).eq(b.ones_coords(16));
+    for &index in [0, 7, 8, 13, 19].iter() {
+        assert!(same(&timeline.get(index).unwrap(), &generations[index]));
+    }
+    assert!(timeline.iter().zip(generations.iter()).all(|(a, b)| same(&a, b)));
+    assert_eq!(timeline.iter_from(13).count(), 7);
+    assert!(same(timeline.last().unwrap(), &generations[19]));
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn animated_export() {
+    use bitboards::animation::{write_apng, write_gif, AnimationOptions};
+
+    let grid = Grid::new(8);
+    let mut board = Bitboard::<64>::new();
+    for x in 2..5 {
+        board.set(grid.index(x, 3));
+    }
+    let mut frames = vec![board];
+    for _ in 0..3 {
+        let next = grid.automaton_step(frames.last().unwrap(), &Rule::LIFE);
+        frames.push(next);
+    }
+    let options = AnimationOptions {
+        scale: 4,
+        frame_delay_ms: 200,
+    };
+
+    let mut gif = Vec::new();
+    write_gif(&mut gif, &frames, 8, &options).unwrap();
+    assert!(gif.starts_with(b"GIF89a"));
+    assert_eq!(u16::from_le_bytes([gif[6], gif[7]]), 32);
+
+    let mut apng = Vec::new();
+    write_apng(&mut apng, frames.iter(), 8, &options).unwrap();
+    assert!(apng.starts_with(b"\x89PNG"));
+    assert!(apng.windows(4).any(|chunk| chunk == b"acTL"));
+    assert!(write_apng(&mut Vec::new(), Vec::<Bitboard<64>>::new(), 8, &options).is_err());
+
+    // the dimensions are checked before anything is drawn
+    let huge = AnimationOptions {
+        scale: 1 << 30,
+        ..options
+    };
+    let error = write_apng(&mut Vec::new(), frames.iter(), 8, &huge).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn board_stats() {
+    let mut board = Bitboard::<100>::new();
+    for &(x, y) in [(1, 2), (3, 2), (8, 7), (4, 6)].iter() {
+        board.set(y * 10 + x);
+    }
+    let stats = board.stats(10);
+    assert_eq!(stats.count, 4);
+    assert!((stats.density - 0.04).abs() < 1e-9);
+    assert_eq!(
+        stats.bounding_box,
+        Some(grid::Rectangle {
+            x: 1,
+            y: 2,
+            width: 8,
+            height: 6
+        })
+    );
+    assert_eq!(stats.centroid, Some((4.0, 4.25)));
+    assert_eq!(stats.quadrant_counts, [2, 0, 1, 1]);
+
+    let empty = Bitboard::<100>::new().stats(10);
+    assert_eq!((empty.count, empty.bounding_box, empty.centroid), (0, None, None));
+
+    // lines longer than the bitboard, there is no cell at all
+    let degenerate = board.stats(101);
+    assert_eq!((degenerate.count, degenerate.density, degenerate.bounding_box), (0, 0.0, None));
+}
+
+#[test]
+fn runtime_sized_bitboard() {
+    // a 137x93 map
+    let mut board = DynBitboard::new(137 * 93);
+    assert_eq!(board.len(), 12741);
+    assert!(board.is_empty());
+    board.set(0);
+    board.set(12740);
+    board.set_field(60, 10, 0b11);
+    assert!(board[61] && board.is_unset(62) && board.is_set(12740));
+    assert_eq!(board.ones().collect::<Vec<_>>(), vec![0, 60, 61, 12740]);
+    assert_eq!(board.view(60..70).count_ones(), 2);
+
+    // the junk stays clear
+    board.flip();
+    assert_eq!(board.ones().count(), 12741 - 4);
+    assert_eq!(board.ones().last(), Some(12739));
+    board.flip();
+
+    let reversed = board.reverse_bits();
+    assert_eq!(reversed.ones().collect::<Vec<_>>(), vec![0, 12679, 12680, 12740]);
+    let mut union = reversed.clone();
+    union += board.clone();
+    union -= reversed;
+    assert_eq!(union.ones().collect::<Vec<_>>(), vec![60, 61]);
+    assert_eq!(board.reverse_bits().reverse_bits(), board);
+}
+
+#[test]
+fn growable_bitboard() {
+    let mut board = DynBitboard::new(0);
+    for i in 0..130 {
+        board.push(i % 3 == 0);
+    }
+    assert_eq!(board.len(), 130);
+    assert_eq!(board.ones().count(), 44);
+    assert!(board.is_set(129) && board.is_unset(128));
+
+    board.resize(200, true);
+    assert_eq!(board.ones().count(), 44 + 70);
+    assert!(board.is_set(130) && board.is_set(199));
+    board.truncate(100);
+    assert_eq!(board.len(), 100);
+    assert_eq!(board.ones().last(), Some(99));
+    // the truncated bits don't come back when growing again
+    board.resize(150, false);
+    assert_eq!(board.ones().last(), Some(99));
+    board.resize(10, true);
+    assert_eq!(board.ones().collect::<Vec<_>>(), vec![0, 3, 6, 9]);
+}
+
+#[test]
+fn dyn_and_const_bitboards() {
+    use std::convert::TryFrom;
+
+    let mut fixed = Bitboard::<100>::new();
+    fixed.set(3);
+    fixed.set(99);
+    let mut dynamic = DynBitboard::from(&fixed);
+    assert_eq!(dynamic.len(), 100);
+    assert!(dynamic == fixed && fixed == dynamic);
+
+    // the junk of the const sized bitboard doesn't leak
+    fixed.flip();
+    let flipped = DynBitboard::from(&fixed);
+    assert_eq!(flipped.ones().count(), 98);
+
+    dynamic += &fixed;
+    assert_eq!(dynamic.ones().count(), 100);
+    dynamic -= &fixed;
+    fixed -= &dynamic;
+    assert!(fixed.is_unset(3) && fixed.is_set(4));
+
+    let mut small = Bitboard::<100>::new();
+    small.set(4);
+    small.set(50);
+    let mut other = DynBitboard::new(100);
+    other.set(50);
+    other.set(60);
+    assert_eq!((small & &other).ones().collect::<Vec<_>>(), [50]);
+    assert_eq!((&other | &small).ones().collect::<Vec<_>>(), [4, 50, 60]);
+    assert_eq!((small ^ &other).ones().collect::<Vec<_>>(), [4, 60]);
+    assert_eq!((&other - &small).ones().collect::<Vec<_>>(), [60]);
+    other &= &small;
+    assert_eq!(other.ones().collect::<Vec<_>>(), [50]);
+    other ^= &fixed;
+    assert_eq!(other.count_ones(), 97);
+    small ^= &other;
+    small |= &DynBitboard::new(100);
+    assert!(small.is_unset(4) && small.is_set(5) && small.is_set(50));
+    assert_eq!(small.count_ones(), 97);
+
+    let back = Bitboard::<100>::try_from(dynamic).unwrap();
+    assert!(back.is_set(99) && back.is_unset(98));
+    let wrong = DynBitboard::new(101);
+    assert_eq!(Bitboard::<100>::try_from(wrong).err().map(|board| board.len()), Some(101));
+}
+
+#[test]
+fn dyn_bitboard_operators() {
+    use std::cmp::Ordering;
+
+    let mut a = DynBitboard::new(70);
+    let mut b = DynBitboard::new(70);
+    for &index in &[1, 64, 69] {
+        a.set(index);
+    }
+    for &index in &[1, 2, 69] {
+        b.set(index);
+    }
+
+    assert_eq!((&a & &b).ones().collect::<Vec<_>>(), [1, 69]);
+    assert_eq!((&a | &b).ones().collect::<Vec<_>>(), [1, 2, 64, 69]);
+    assert_eq!((&a ^ &b).ones().collect::<Vec<_>>(), [2, 64]);
+    // the complement keeps the junk bits unset
+    let complement = !&a;
+    assert_eq!(complement.count_ones(), 67);
+    assert_eq!(complement.last_set(), Some(68));
+
+    assert_eq!((&a << 5).ones().collect::<Vec<_>>(), [6, 69]);
+    assert_eq!((&a >> 1).ones().collect::<Vec<_>>(), [0, 63, 68]);
+    let mut shifted = a.clone();
+    shifted <<= 64;
+    shifted >>= 63;
+    assert_eq!(shifted.ones().collect::<Vec<_>>(), [2]);
+
+    let mut c = a.clone();
+    c &= &b;
+    c |= &DynBitboard::new(70);
+    assert!(c.is_subset(&a) && c.is_subset(&b) && a.is_superset(&c));
+    assert!(a.intersects(&b) && complement.is_disjoint(&a));
+    assert_eq!(a.intersection(&b), c);
+    assert_eq!(a.union(&b), &a | &b);
+    assert_eq!(a.difference(&b).ones().collect::<Vec<_>>(), [64]);
+
+    assert_eq!(c.partial_cmp(&a), Some(Ordering::Less));
+    assert_eq!(a.partial_cmp(&b), None);
+    assert_eq!(a.partial_cmp(&DynBitboard::new(71)), None);
+    let longer = DynBitboard::new(71);
+    assert!(!a.intersects(&longer) && !a.is_disjoint(&longer));
+    assert!(!longer.is_subset(&a) && !a.is_superset(&longer) && a != longer);
+}
+
+/// Sieve of Eratosthenes written once for all the bitboard flavors
+fn sieve<B: BitboardOps>(empty: &B) -> B {
+    let mut primes = empty.empty_like();
+    primes.flip();
+    primes.unset(0);
+    primes.unset(1);
+    let len = primes.bit_len();
+    for i in 2..len {
+        if primes.is_set(i) {
+            let mut multiples = empty.empty_like();
+            for multiple in (2 * i..len).step_by(i) {
+                multiples.set(multiple);
+            }
+            primes.difference_with(&multiples);
+        }
+    }
+    primes
+}
+
+#[test]
+fn generic_bitboard_ops() {
+    let fixed = sieve(&Bitboard::<50>::new());
+    let boxed = sieve(&Bitboard::<50>::new_boxed());
+    let dynamic = sieve(&DynBitboard::new(50));
+    let expected = vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47];
+    assert_eq!(BitboardOps::ones(&fixed).collect::<Vec<_>>(), expected);
+    assert_eq!(BitboardOps::ones(&boxed).collect::<Vec<_>>(), expected);
+    assert_eq!(BitboardOps::ones(&dynamic).collect::<Vec<_>>(), expected);
+    assert_eq!(BitboardOps::count_ones(&fixed), 15);
+
+    let mut odd = DynBitboard::new(50);
+    for i in (1..50).step_by(2) {
+        odd.set(i);
+    }
+    let mut odd_primes = dynamic.clone();
+    odd_primes.intersect_with(&odd);
+    assert_eq!(BitboardOps::count_ones(&odd_primes), 14);
+    odd_primes.union_with(&dynamic);
+    assert_eq!(odd_primes, dynamic);
+}
+
+#[test]
+fn apply_one_mask_to_many_boards() {
+    // the domains of a few variables, eliminating the values of a mask from all of them
+    let mut domains = vec![Bitboard::<10000>::new(); 12];
+    for (i, domain) in domains.iter_mut().enumerate() {
+        domain.set(i);
+        domain.set(9000 + i);
+    }
+    let mut mask = Bitboard::<10000>::new();
+    for i in (0..10000).step_by(2) {
+        mask.set(i);
+    }
+    Bitboard::<10000>::apply_mask_all(&mut domains, &mask, |a, b| a & !b);
+    for (i, domain) in domains.iter().enumerate() {
+        assert_eq!(domain.is_set(i), i % 2 == 1);
+        assert_eq!(domain.is_set(9000 + i), i % 2 == 1);
+    }
+    Bitboard::<10000>::apply_mask_all(&mut domains, &mask, |a, b| a | b);
+    assert!(domains.iter().all(|domain| domain.is_set(9998) && domain.is_unset(9999)));
+}
+
+#[test]
+fn transpose_64_words() {
+    let mut seed = 42u64;
+    let mut rows = [0u64; 64];
+    for row in rows.iter_mut() {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        *row = seed;
+    }
+    let original = rows;
+    planes::transpose_64x64(&mut rows);
+    for (i, &original_row) in original.iter().enumerate() {
+        for (j, &row) in rows.iter().enumerate() {
+            assert_eq!(row >> i & 1, original_row >> j & 1);
+        }
+    }
+    planes::transpose_64x64(&mut rows);
+    assert_eq!(rows, original);
+
+    // a per cell counter over 64 boards: the word k of the slices tells which boards have k set
+    let mut boards: [Bitboard<64>; 64] = std::array::from_fn(|_| Bitboard::<64>::new());
+    boards[5].set(10);
+    boards[63].set(10);
+    boards[7].set(0);
+    Bitboard::<64>::transpose_all(&mut boards);
+    assert!(boards[10].is_set(5) && boards[10].is_set(63) && boards[0].is_set(7));
+    assert!(boards[5].is_empty());
+}
+
+#[cfg(feature = "gpu")]
+#[test]
+fn gpu_backend() {
+    use bitboards::gpu::{GpuContext, GpuOp};
+    use std::convert::TryFrom;
+
+    // nothing to check without a GPU
+    let context = match GpuContext::new() {
+        Some(context) => context,
+        None => return,
+    };
+
+    let grid = Grid::new(100);
+    let mut board = DynBitboard::new(100 * 80 + 7);
+    let mut mask = DynBitboard::new(100 * 80 + 7);
+    for &(x, y) in [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2), (50, 40), (51, 40), (52, 40)].iter() {
+        board.set(grid.index(x, y));
+    }
+    for i in (0..board.len()).step_by(3) {
+        mask.set(i);
+    }
+
+    let mut gpu_board = context.upload(&board);
+    assert_eq!(context.download(&gpu_board), board);
+    assert_eq!(context.count_ones(&gpu_board), 8);
+
+    // same generations as on the CPU, with blinkers across the edges
+    let mut cpu_board = Bitboard::<8007>::try_from(board.clone()).unwrap();
+    for &(x, y) in [(99, 10), (0, 10), (1, 10), (5, 79), (5, 0), (5, 1)].iter() {
+        grid.set_at(&mut cpu_board, Coord(x, y));
+    }
+    let gpu_wrapping = context.upload(&DynBitboard::from(&cpu_board));
+    for &topology in [Topology::Bounded, Topology::Toroidal, Topology::Cylindrical].iter() {
+        let cpu_next = Grid::with_topology(100, topology).automaton_step(&cpu_board, &Rule::LIFE);
+        let gpu_next = context.automaton_step(&gpu_wrapping, 100, topology, &Rule::LIFE);
+        assert!(context.download(&gpu_next) == cpu_next);
+    }
+
+    let gpu_mask = context.upload(&mask);
+    context.combine(&mut gpu_board, &gpu_mask, GpuOp::Or);
+    let mut expected = board.clone();
+    expected += mask;
+    assert_eq!(context.download(&gpu_board), expected);
+}
+
+#[test]
+fn streaming_bits() {
+    // more than one chunk of bytes, with junk bits set
+    let mut board = Bitboard::<100_003>::new_boxed();
+    board.flip();
+    for i in (0..100_003).step_by(7) {
+        board.unset(i);
+    }
+    let mut bytes = Vec::new();
+    board.write_bits_to(&mut bytes).unwrap();
+    assert_eq!(bytes.len(), 12501);
+    assert_eq!(bytes[12500], 0b011);
+
+    let read = Bitboard::<100_003>::read_boxed_from(&bytes[..]).unwrap();
+    assert_eq!(read.checksum64(), board.checksum64());
+    let dynamic = DynBitboard::read_bits_from(&bytes[..], 100_003).unwrap();
+    assert!(dynamic == *board);
+
+    let mut written = Vec::new();
+    dynamic.write_bits_to(&mut written).unwrap();
+    assert_eq!(written, bytes);
+    let error = DynBitboard::read_bits_from(&bytes[..100], 1000).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn heap_introspection() {
+    let mut hybrid = hybrid::HybridBitboard::new();
+    for i in 0..10_000 {
+        hybrid.set(i);
+    }
+    hybrid.set(1 << 20);
+    assert_eq!(hybrid.container_counts(), (1, 1));
+    let dense_size = hybrid.heap_size_bytes();
+    assert!(dense_size >= 8192);
+    assert!((hybrid.density() - 10_001.0 / 131_072.0).abs() < 1e-12);
+    // the chunk became sparse, it is stored as a sorted array after the shrink
+    for i in 100..10_000 {
+        hybrid.unset(i);
+    }
+    hybrid.shrink_to_fit();
+    assert_eq!(hybrid.container_counts(), (2, 0));
+    assert!(hybrid.heap_size_bytes() < dense_size / 10);
+
+    let mut dynamic = DynBitboard::new(64_000);
+    dynamic.set(5);
+    dynamic.truncate(64);
+    assert_eq!(dynamic.heap_size_bytes(), 8000);
+    dynamic.shrink_to_fit();
+    assert_eq!(dynamic.heap_size_bytes(), 8);
+    assert!((dynamic.density() - 1.0 / 64.0).abs() < 1e-12);
+
+    let mut board = Bitboard::<6400>::new();
+    board.set(1);
+    let mut compressed = compressed::CompressedBitboard::<6400>::from_bitboard(&board);
+    let grown_size = compressed.heap_size_bytes();
+    compressed.shrink_to_fit();
+    assert!(compressed.heap_size_bytes() <= grown_size && compressed.heap_size_bytes() < 100);
+    let summary = summary::SummaryBitboard::<6400>::from_bitboard(Box::new(board));
+    assert!(summary.heap_size_bytes() >= 800);
+    assert!((summary.density() - 1.0 / 6400.0).abs() < 1e-12);
+}
+
+#[cfg(feature = "allocator_api")]
+#[test]
+fn custom_allocator() {
+    use std::alloc::{AllocError, Allocator, Global, Layout};
+    use std::cell::Cell;
+    use std::ptr::NonNull;
+    use std::rc::Rc;
+
+    // counts the live bytes allocated through it
+    #[derive(Clone, Default)]
+    struct Counting(Rc<Cell<usize>>);
+    unsafe impl Allocator for Counting {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.0.set(self.0.get() + layout.size());
+            Global.allocate(layout)
+        }
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.0.set(self.0.get() - layout.size());
+            unsafe { Global.deallocate(ptr, layout) }
+        }
+    }
+
+    let allocator = Counting::default();
+    let mut boxed = Bitboard::<6400>::new_boxed_in(allocator.clone());
+    assert_eq!(allocator.0.get(), 800);
+    boxed.set(6399);
+    assert!(boxed.is_set(6399) && boxed.is_unset(0));
+
+    let mut dynamic = DynBitboard::new_in(1000, allocator.clone());
+    assert_eq!(allocator.0.get(), 800 + 128);
+    dynamic.set(3);
+    dynamic.resize(2000, false);
+    let reversed = dynamic.reverse_bits();
+    assert!(reversed.is_set(1996));
+    let mut global = DynBitboard::new(2000);
+    global.set(3);
+    assert_eq!(dynamic, global);
+
+    drop((boxed, dynamic, reversed));
+    assert_eq!(allocator.0.get(), 0);
+}
+
+#[test]
+fn ascii_grid() {
+    use render::AsciiGridError;
+
+    let level = "#..@\n.##.\n\n...#\n";
+    let board = Bitboard::<16>::from_ascii_grid(level, &['#', '@']).unwrap();
+    assert_eq!(BitboardOps::ones(&board).collect::<Vec<_>>(), [0, 3, 5, 6, 11]);
+    assert_eq!(board.to_ascii_grid(4), "#..#\n.##.\n...#\n....\n");
+    let parsed = Bitboard::<16>::from_ascii_grid(&board.to_ascii_grid(4), &['#']).unwrap();
+    assert!(BitboardOps::ones(&parsed).eq(BitboardOps::ones(&board)));
+
+    let dynamic = DynBitboard::from_ascii_grid("#.#\r\n.#.\r\n", &['#']).unwrap();
+    assert_eq!(dynamic.len(), 6);
+    assert_eq!(dynamic.to_ascii_grid(3), "#.#\n.#.\n");
+
+    assert_eq!(
+        DynBitboard::from_ascii_grid("#..\n.#\n", &['#']),
+        Err(AsciiGridError::RaggedLine { line: 1, len: 2, expected: 3 })
+    );
+    assert_eq!(
+        Bitboard::<8>::from_ascii_grid("...#\n....\n...#\n", &['#']).err(),
+        Some(AsciiGridError::TooManyCells { cells: 12, bits: 8 })
+    );
+}
+
+#[test]
+fn big_integer_multiplication() {
+    use std::convert::TryFrom;
+
+    let to_u128 = |board: &BitboardInternal<2, 63>| DynBitboard::from(board).get_field(0, 64) as u128
+        | (DynBitboard::from(board).get_field(64, 64) as u128) << 64;
+    let mut a = Bitboard::<64>::new();
+    a.set_field(0, 64, 0xdead_beef_1234_5678);
+    let mut b = Bitboard::<64>::new();
+    b.set_field(0, 64, 0xffff_ffff_ffff_fff1);
+    assert_eq!(to_u128(&a.widening_mul(&b)), 0xdead_beef_1234_5678u128 * 0xffff_ffff_ffff_fff1);
+
+    // (2^512 - 1)^2 = 2^1024 - 2^513 + 1
+    let mut max = Bitboard::<512>::new();
+    max.flip();
+    let square: Bitboard<1024> = max.widening_mul(&max);
+    let expected = (0..1024).map(|i| i == 0 || i >= 513);
+    assert!(expected.enumerate().all(|(i, bit)| square.is_set(i) == bit));
+
+    // the junk bits are not part of the integer
+    let mut odd = Bitboard::<100>::new();
+    odd.flip();
+    let mut one = Bitboard::<100>::new();
+    one.set(0);
+    let product = DynBitboard::from(&odd.widening_mul::<4>(&one));
+    assert_eq!(product.ones().count(), 100);
+
+    // (2^99 + 3) * 6 = 3 * 2^100 + 18, truncated to 100 bits
+    let mut x = Bitboard::<100>::new();
+    x.set(99);
+    x.set_field(0, 2, 3);
+    let mut six = Bitboard::<100>::new();
+    six.set_field(0, 3, 6);
+    x *= &six;
+    let x = DynBitboard::from(&x);
+    assert_eq!(x.ones().collect::<Vec<_>>(), [1, 4]);
+    assert!(Bitboard::<100>::try_from(x).is_ok());
+}
+
+#[test]
+fn bit_order() {
+    // a network bitmap where the first bit is the MSB of the first byte
+    let bitmap = [0b1000_0001, 0b0100_0000];
+    let board = Bitboard::<12>::from_ordered_bytes(&bitmap, BitOrder::MsbFirst);
+    assert_eq!(BitboardOps::ones(&board).collect::<Vec<_>>(), [0, 7, 9]);
+    assert_eq!(board.to_ordered_bytes(BitOrder::MsbFirst), bitmap);
+    assert_eq!(board.to_ordered_bytes(BitOrder::LsbFirst), [0b1000_0001, 0b10]);
+
+    let dynamic = DynBitboard::from_ordered_bytes(&bitmap, 12, BitOrder::MsbFirst);
+    assert!(dynamic == board);
+    assert_eq!(dynamic.to_ordered_bytes(BitOrder::MsbFirst), bitmap);
+
+    let board = Bitboard::<100>::from_ordered_words(&[1 << 63, 1 << 62 | 1], BitOrder::MsbFirst);
+    assert_eq!(BitboardOps::ones(&board).collect::<Vec<_>>(), [0, 65]);
+    assert_eq!(board.to_ordered_words(BitOrder::LsbFirst), [1, 2]);
+    assert_eq!(board.to_ordered_words(BitOrder::MsbFirst), [1 << 63, 1 << 62]);
+}
+
+#[test]
+fn typed_indices() {
+    let grid = Grid::<1, 63>::new(8);
+    let mut board = Bitboard::<64>::new();
+    grid.set_at(&mut board, Coord(3, 2));
+    grid.set_at(&mut board, 63usize);
+    assert!(board.is_set(19) && grid.is_set_at(&board, Coord(7, 7)));
+    assert_eq!(grid.ones_at::<Coord>(&board).collect::<Vec<_>>(), [Coord(3, 2), Coord(7, 7)]);
+    assert_eq!(board.ones_as::<usize>(8).collect::<Vec<_>>(), [19, 63]);
+    assert_eq!(grid.neighbor_at(Coord(3, 2), Direction::North), Some(Coord(3, 1)));
+    assert_eq!(grid.neighbor_at(Coord(7, 7), Direction::East), None);
+    grid.unset_at(&mut board, Coord::from((3, 2)));
+    assert_eq!(grid.flat(Coord(1, 1)), 9);
+    assert_eq!(grid.typed::<Coord>(9), Coord(1, 1));
+    assert!(board.is_unset(19));
+
+    #[cfg(feature = "chess")]
+    {
+        use chess::Square;
+        let e4 = Square::from_name("e4").unwrap();
+        grid.set_at(&mut board, e4);
+        assert_eq!(grid.ones_at::<Square>(&board).map(Square::name).collect::<Vec<_>>(), ["e4", "h8"]);
+        board.unset(e4);
+        board.set(Square::from_name("b1").unwrap());
+        assert!(board.is_set(Square(1)) && board[Square(63)] && !board[e4]);
+        assert_eq!(board.ones_as::<usize>(8).collect::<Vec<_>>(), [1, 63]);
+    }
+
+    let mut dynamic = DynBitboard::new(100);
+    dynamic.set(42usize);
+    dynamic.assign(7usize, true);
+    assert!(dynamic[42usize] && dynamic.is_set(7usize) && dynamic.is_unset(8usize));
+}
+
+#[test]
+#[should_panic]
+fn typed_index_out_of_line() {
+    let grid = Grid::<1, 63>::new(8);
+    grid.flat(Coord(8, 0));
+}
+
+#[test]
+fn bit_iterator() {
+    let mut board = Bitboard::<200>::new();
+    for &i in &[0, 63, 64, 130, 199] {
+        board.set(i);
+    }
+    assert_eq!(board.iter().collect::<Vec<_>>(), [0, 63, 64, 130, 199]);
+    assert_eq!(board.iter().rev().collect::<Vec<_>>(), [199, 130, 64, 63, 0]);
+    assert_eq!(board.iter().count(), 5);
+
+    // both ends meet in the middle word without yielding a bit twice
+    let mut iter = board.iter();
+    assert_eq!((iter.next(), iter.next_back(), iter.next_back()), (Some(0), Some(199), Some(130)));
+    assert_eq!((iter.next(), iter.next(), iter.next_back()), (Some(63), Some(64), None));
+    assert_eq!(iter.next(), None);
+
+    // no junk bit is yielded after flip
+    let mut full = Bitboard::<70>::new();
+    full.flip();
+    assert_eq!(full.iter().next_back(), Some(69));
+    assert_eq!((&full).into_iter().count(), 70);
+    assert_eq!(full.into_iter().filter(|i| i % 2 == 0).count(), 35);
+
+    let mut dynamic = DynBitboard::new(100);
+    dynamic.set(3);
+    dynamic.set(99);
+    assert_eq!((&dynamic).into_iter().rev().collect::<Vec<_>>(), [99, 3]);
+}
+
+#[test]
+fn bitwise_operators() {
+    let mut a = Bitboard::<100>::new();
+    a.set(1);
+    a.set(70);
+    let mut b = Bitboard::<100>::new();
+    b.set(70);
+    b.set(99);
+    assert!((a & b).iter().eq([70]));
+    assert!((a | b).iter().eq([1, 70, 99]));
+    assert!((a ^ b).iter().eq([1, 99]));
+
+    // the complement keeps the junk bits clear
+    let full = !Bitboard::<100>::default();
+    assert_eq!(full.iter().count(), 100);
+    assert!((!full).is_empty());
+    assert!(!full == Bitboard::<100>::new());
+    let mut c = a;
+    c ^= a;
+    assert!(c.is_empty() && c == Bitboard::<100>::default());
+    c |= &b;
+    c &= !a;
+    assert!(c.iter().eq([99]));
+    assert!(c != b);
+
+    // equality ignores the junk bits
+    let mut junk = Bitboard::<100>::new();
+    junk.set(127);
+    assert!(junk == Bitboard::<100>::new());
+}
+
+#[test]
+fn shift_operators() {
+    let mut board = Bitboard::<150>::new();
+    board.set(0);
+    board.set(63);
+    board.set(100);
+
+    let left = board << 65;
+    assert!(left.iter().eq([65, 128]));
+    assert!((&board << 1).iter().eq([1, 64, 101]));
+    let right = board >> 63;
+    assert!(right.iter().eq([0, 37]));
+    assert!((board >> 150).is_empty() && (board << 1000).is_empty());
+    assert!(board << 0 == board);
+
+    // the bits shifted past the size don't come back
+    let mut round_trip = board;
+    round_trip <<= 80;
+    round_trip >>= 80;
+    assert!(round_trip.iter().eq([0, 63]));
+
+    // the junk bits are not shifted in
+    let mut full = !Bitboard::<150>::new();
+    full >>= 10;
+    assert_eq!(full.iter().count(), 140);
+    assert_eq!(full.iter().next_back(), Some(139));
+    let mut junk = Bitboard::<150>::new();
+    junk.set(170);
+    junk >>= 10;
+    assert!(junk.is_empty());
+}
+
+#[test]
+fn rank_queries() {
+    let mut board = Bitboard::<300>::new();
+    assert_eq!((board.count_ones(), board.first_set(), board.last_set()), (0, None, None));
+    assert_eq!(board.select(0), None);
+    for &i in &[5, 64, 65, 200, 299] {
+        board.set(i);
+    }
+    // a junk bit is never counted
+    board.set(310);
+    assert_eq!(board.count_ones(), 5);
+    assert_eq!((board.first_set(), board.last_set()), (Some(5), Some(299)));
+    let selected: Vec<_> = (0..6).map(|n| board.select(n)).collect();
+    assert_eq!(selected, [Some(5), Some(64), Some(65), Some(200), Some(299), None]);
+
+    let mut dynamic = DynBitboard::from(&board);
+    dynamic.unset(5);
+    assert_eq!(dynamic.count_ones(), 4);
+    assert_eq!((dynamic.first_set(), dynamic.last_set()), (Some(64), Some(299)));
+    assert_eq!((dynamic.select(2), dynamic.select(4)), (Some(200), None));
+}
+
+#[test]
+fn range_operations() {
+    let mut board = Bitboard::<300>::new();
+    board.set_range(60..200);
+    assert_eq!((board.count_ones(), board.first_set(), board.last_set()), (140, Some(60), Some(199)));
+    board.unset_range(62..130);
+    assert!(board.iter().take(3).eq([60, 61, 130]));
+    board.toggle_range(0..300);
+    assert_eq!(board.count_ones(), 300 - 72);
+    assert!(board.is_unset(60) && board.is_set(100) && board.is_unset(199) && board.is_set(299));
+    // within a single word and empty ranges
+    let mut small = Bitboard::<64>::new();
+    small.set_range(3..5);
+    small.set_range(10..10);
+    small.toggle_range(4..64);
+    assert!(small.iter().take(3).eq([3, 5, 6]));
+    assert_eq!(small.count_ones(), 60);
+
+    // lines longer than a word, straddling words
+    let mut lines = Bitboard::<400>::new();
+    lines.set_whole_line(1, 100);
+    assert!(lines.iter().eq(100..200));
+    lines.set_whole_line(0, 7);
+    assert!(lines.iter().eq((0..7).chain(100..200)));
+
+    let mut dynamic = DynBitboard::new(130);
+    dynamic.set_range(0..130);
+    dynamic.unset_range(64..128);
+    dynamic.toggle_range(127..130);
+    assert!(dynamic.iter().eq((0..64).chain([127])));
+}
+
+#[test]
+#[should_panic]
+fn range_out_of_the_bitboard() {
+    Bitboard::<100>::new().set_range(50..101);
+}
+
+#[test]
+fn board_2d() {
+    type Board = Bitboard2D<10, 7>;
+    assert_eq!((Board::WIDTH, Board::HEIGHT), (10, 7));
+    let mut board = Board::new();
+    board.set(Coord(9, 0));
+    board.set(Coord(0, 3));
+    board.set(64usize);
+    assert!(board.is_set(Coord(9, 0)) && board.is_set(Coord(4, 6)) && board.board().is_set(30));
+
+    // the cells don't wrap to the next or previous line
+    assert!(board.shift_east().board().iter().eq([31, 65]));
+    assert!(board.shift_west().board().iter().eq([8, 63]));
+    assert!(board.shift_north().board().iter().eq([20, 54]));
+    assert!(board.shift_south().board().iter().eq([19, 40]));
+    assert!(board.shift(Direction::SouthWest).board().iter().eq([18]));
+
+    assert!(Board::row_mask(6).board().iter().eq(60..70));
+    assert!(Board::column_mask(3).board().iter().eq((0..7).map(|y| y * 10 + 3)));
+
+    let mut blinker = Board::new();
+    for x in 3..6 {
+        blinker.set(Coord(x, 2));
+    }
+    assert_eq!(blinker.neighbor_count(Coord(4, 1), Connectivity::Eight), 3);
+    assert_eq!(blinker.neighbor_count(Coord(4, 1), Connectivity::Four), 1);
+    assert_eq!(blinker.neighbor_count(69usize, Connectivity::Eight), 0);
+    let born = blinker.with_neighbor_count(3, Connectivity::Eight);
+    assert!(born.board().iter().eq([14, 34]));
+    // more neighbors than directions, not taken modulo the counters
+    assert_eq!(blinker.with_neighbor_count(16, Connectivity::Eight), Board::new());
+    assert_eq!(blinker.with_neighbor_count(5, Connectivity::Four), Board::new());
+    // the same as a step of the automaton on the equivalent grid, for the dead cells
+    let next = Board::grid().automaton_step(blinker.board(), &Rule::LIFE);
+    assert!(next.iter().eq([14, 24, 34]));
+}
+
+#[test]
+fn raw_words_and_hex() {
+    use hex::ParseHexError;
+
+    let board = Bitboard::<70>::from_words([0xff00, 0x21]);
+    assert_eq!(board.as_words(), &[0xff00, 0x21]);
+    assert_eq!(board.to_hex(), "21000000000000ff00");
+    let parsed = Bitboard::<70>::from_hex("21000000000000FF00").unwrap();
+    assert_eq!(parsed.into_words(), [0xff00, 0x21]);
+    assert_eq!(Bitboard::<70>::from_hex(&Bitboard::<70>::new().to_hex()), Ok(Bitboard::<70>::new()));
+
+    assert_eq!(Bitboard::<70>::from_hex("ff00"), Err(ParseHexError::Length { len: 4, expected: 18 }));
+    assert_eq!(Bitboard::<70>::from_hex("21000000000000fg00"), Err(ParseHexError::InvalidDigit { offset: 15 }));
+    // 70 bits leave 2 bits in the first digit
+    assert_eq!(Bitboard::<70>::from_hex("40000000000000ff00"), Err(ParseHexError::JunkBits));
+
+    let mut small = Bitboard::<12>::new();
+    small.set(0);
+    small.set(11);
+    assert_eq!(format!("{}", small), "100000000001");
+    assert_eq!(format!("{:?}", small), "Bitboard<12>\n100000000001");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    let mut board = Bitboard::<70>::new();
+    board.set(0);
+    board.set(69);
+    let json = serde_json::to_string(&board).unwrap();
+    assert_eq!(json, "[1,32]");
+    assert_eq!(serde_json::from_str::<Bitboard<70>>(&json).unwrap(), board);
+    // junk bits and wrong lengths are rejected
+    assert!(serde_json::from_str::<Bitboard<70>>("[1,64]").is_err());
+    assert!(serde_json::from_str::<Bitboard<70>>("[1]").is_err());
+
+    let dynamic = DynBitboard::from(&board);
+    let json = serde_json::to_string(&dynamic).unwrap();
+    assert_eq!(json, r#"{"len":70,"words":[1,32]}"#);
+    assert_eq!(serde_json::from_str::<DynBitboard>(&json).unwrap(), dynamic);
+    assert!(serde_json::from_str::<DynBitboard>(r#"{"len":70,"words":[1,64]}"#).is_err());
+    assert!(serde_json::from_str::<DynBitboard>(r#"{"len":70,"words":[1]}"#).is_err());
+}
+
+#[test]
+fn const_construction() {
+    const FILE_A: Bitboard<64> = bitboard!(64; 0, 8, 16, 24, 32, 40, 48, 56);
+    const RANK_1: Bitboard<64> = Bitboard::<64>::from_indices(&[0, 1, 2, 3, 4, 5, 6, 7]);
+    const CORNERS: Bitboard<64> = Bitboard::<64>::new().with_bit(0).with_bit(7).with_bit(56).with_bit(63);
+    const WIDE: Bitboard<130> = Bitboard::<130>::new().with_bit(129).with_bit(3).without_bit(3);
+    const _: () = assert!(FILE_A.has_bit(0) && !FILE_A.has_bit(1));
+
+    assert!(FILE_A.iter().eq((0..64).step_by(8)));
+    assert!((FILE_A & RANK_1).iter().eq([0]));
+    assert_eq!(CORNERS.count_ones(), 4);
+    assert!(WIDE.iter().eq([129]));
+    assert_eq!(bitboard!(10;).count_ones(), 0);
+}
+
+#[test]
+fn bulk_operations() {
+    // more words than a vector plus a remainder, with the simd feature or not
+    let mut a = Bitboard::<1500>::new();
+    let mut b = Bitboard::<1500>::new();
+    for i in (0..1500).step_by(3) {
+        a.set(i);
+    }
+    for i in (0..1500).step_by(5) {
+        b.set(i);
+    }
+    assert_eq!(((a & b).count_ones(), (a | b).count_ones(), (a ^ b).count_ones()), (100, 700, 600));
+    let mut difference = a;
+    difference -= b;
+    assert_eq!(difference.count_ones(), 400);
+    assert!(!a.is_empty() && (a & !a).is_empty());
+    let mut c = a;
+    assert!(c == a);
+    c.unset(1497);
+    assert!(c != a);
+    c.set(1497);
+    c.unset(3);
+    assert!(c != a);
+
+    let mut dynamic = DynBitboard::from(&a);
+    assert_eq!(dynamic.count_ones(), 500);
+    dynamic.flip();
+    dynamic.flip();
+    assert!(!dynamic.is_empty() && DynBitboard::new(1500).is_empty());
+}
+
+#[test]
+fn set_relations() {
+    use std::cmp::Ordering;
+    use std::collections::HashSet;
+
+    let small = bitboard!(100; 3, 70);
+    let large = bitboard!(100; 3, 50, 70, 99);
+    let other = bitboard!(100; 4, 99);
+    assert!(small.is_subset(&large) && large.is_superset(&small) && !large.is_subset(&small));
+    assert!(small.is_disjoint(&other) && large.intersects(&other) && !small.intersects(&other));
+    assert!(Bitboard::<100>::new().is_subset(&small) && small.is_subset(&small));
+
+    assert!(small.union(&other).iter().eq([3, 4, 70, 99]));
+    assert!(large.intersection(&other).iter().eq([99]));
+    assert!(large.difference(&small).iter().eq([50, 99]));
+
+    assert!(small < large && large > small && small <= small);
+    assert_eq!(small.partial_cmp(&other), None);
+    assert_eq!(large.partial_cmp(&large), Some(Ordering::Equal));
+
+    // the junk bits change neither the relations nor the hash
+    let mut junk = small;
+    junk.set(120);
+    assert!(junk.is_subset(&small) && junk == small);
+    let set: HashSet<_> = vec![small, junk, large].into_iter().collect();
+    assert_eq!(set.len(), 2);
+}